@@ -0,0 +1,263 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo, Level};
+
+/// Which way the dialog is laid out (`direction` in the input, or
+/// `--direction`).
+#[derive(Serialize, Deserialize, clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// From the questions' text, then the user's locale.
+    #[default]
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    /// Whether to lay out right to left. `Auto` goes by the first strongly
+    /// directional character in `text`, falling back to the locale.
+    pub fn is_rtl(self, text: &str) -> bool {
+        match self {
+            Self::Ltr => false,
+            Self::Rtl => true,
+            Self::Auto => first_strong(text).unwrap_or_else(locale_rtl),
+        }
+    }
+}
+
+/// Languages written right to left, by ISO 639 code.
+const RTL_LANGUAGES: &[&str] = &["ar", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi"];
+
+fn locale_rtl() -> bool {
+    sys_locale::get_locale().is_some_and(|locale| {
+        let language = locale.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        RTL_LANGUAGES.contains(&language.as_str())
+    })
+}
+
+fn first_strong(text: &str) -> Option<bool> {
+    text.chars().find_map(|c| match bidi_class(c) {
+        BidiClass::L => Some(false),
+        BidiClass::R | BidiClass::AL => Some(true),
+        _ => None,
+    })
+}
+
+/// Whether `c` is written right to left.
+pub fn is_rtl(c: char) -> bool {
+    matches!(bidi_class(c), BidiClass::R | BidiClass::AL | BidiClass::AN)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Joining {
+    /// Joins the letters on both sides.
+    Dual,
+    /// Joins only the letter before it.
+    Right,
+    /// Never joins.
+    None,
+}
+
+/// Arabic letters with the code of their isolated presentation form. The
+/// final form follows it, then the initial and medial forms for letters
+/// that join on both sides.
+const LETTERS: &[(char, u32, Joining)] = &[
+    ('\u{0621}', 0xFE80, Joining::None),
+    ('\u{0622}', 0xFE81, Joining::Right),
+    ('\u{0623}', 0xFE83, Joining::Right),
+    ('\u{0624}', 0xFE85, Joining::Right),
+    ('\u{0625}', 0xFE87, Joining::Right),
+    ('\u{0626}', 0xFE89, Joining::Dual),
+    ('\u{0627}', 0xFE8D, Joining::Right),
+    ('\u{0628}', 0xFE8F, Joining::Dual),
+    ('\u{0629}', 0xFE93, Joining::Right),
+    ('\u{062A}', 0xFE95, Joining::Dual),
+    ('\u{062B}', 0xFE99, Joining::Dual),
+    ('\u{062C}', 0xFE9D, Joining::Dual),
+    ('\u{062D}', 0xFEA1, Joining::Dual),
+    ('\u{062E}', 0xFEA5, Joining::Dual),
+    ('\u{062F}', 0xFEA9, Joining::Right),
+    ('\u{0630}', 0xFEAB, Joining::Right),
+    ('\u{0631}', 0xFEAD, Joining::Right),
+    ('\u{0632}', 0xFEAF, Joining::Right),
+    ('\u{0633}', 0xFEB1, Joining::Dual),
+    ('\u{0634}', 0xFEB5, Joining::Dual),
+    ('\u{0635}', 0xFEB9, Joining::Dual),
+    ('\u{0636}', 0xFEBD, Joining::Dual),
+    ('\u{0637}', 0xFEC1, Joining::Dual),
+    ('\u{0638}', 0xFEC5, Joining::Dual),
+    ('\u{0639}', 0xFEC9, Joining::Dual),
+    ('\u{063A}', 0xFECD, Joining::Dual),
+    ('\u{0641}', 0xFED1, Joining::Dual),
+    ('\u{0642}', 0xFED5, Joining::Dual),
+    ('\u{0643}', 0xFED9, Joining::Dual),
+    ('\u{0644}', 0xFEDD, Joining::Dual),
+    ('\u{0645}', 0xFEE1, Joining::Dual),
+    ('\u{0646}', 0xFEE5, Joining::Dual),
+    ('\u{0647}', 0xFEE9, Joining::Dual),
+    ('\u{0648}', 0xFEED, Joining::Right),
+    // Only isolated and final forms are encoded in this block
+    ('\u{0649}', 0xFEEF, Joining::Right),
+    ('\u{064A}', 0xFEF1, Joining::Dual),
+    // Persian and Urdu additions
+    ('\u{067E}', 0xFB56, Joining::Dual),
+    ('\u{0686}', 0xFB7A, Joining::Dual),
+    ('\u{0698}', 0xFB8A, Joining::Right),
+    ('\u{06A9}', 0xFB8E, Joining::Dual),
+    ('\u{06AF}', 0xFB92, Joining::Dual),
+    ('\u{06CC}', 0xFBFC, Joining::Dual),
+];
+
+const LAM: char = '\u{0644}';
+
+fn letter(c: char) -> Option<(u32, Joining)> {
+    LETTERS.iter().find(|(l, _, _)| *l == c).map(|&(_, form, joining)| (form, joining))
+}
+
+/// Harakat and other marks that sit on a letter without affecting joins.
+fn transparent(c: char) -> bool {
+    matches!(c, '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{06D6}'..='\u{06ED}')
+}
+
+/// Tatweel and zero-width joiner join on both sides without changing shape.
+fn joiner(c: char) -> bool {
+    matches!(c, '\u{0640}' | '\u{200D}')
+}
+
+fn joins_next(c: char) -> bool {
+    joiner(c) || letter(c).is_some_and(|(_, j)| j == Joining::Dual)
+}
+
+fn joins_previous(c: char) -> bool {
+    joiner(c) || letter(c).is_some_and(|(_, j)| j != Joining::None)
+}
+
+/// Isolated form of the lam-alef ligature, for the alef that follows a lam.
+fn lam_alef(c: char) -> Option<u32> {
+    match c {
+        '\u{0622}' => Some(0xFEF5),
+        '\u{0623}' => Some(0xFEF7),
+        '\u{0625}' => Some(0xFEF9),
+        '\u{0627}' => Some(0xFEFB),
+        _ => None,
+    }
+}
+
+/// Replaces Arabic letters with the presentation form for their position
+/// in the word, since egui draws each character on its own.
+fn shape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let Some((isolated, joining)) = letter(c) else {
+            out.push(c);
+            i += 1;
+            continue;
+        };
+        let prev = chars[..i].iter().rev().find(|c| !transparent(**c));
+        let next = (i + 1..chars.len()).find(|&j| !transparent(chars[j]));
+        let from_prev = joining != Joining::None && prev.is_some_and(|&p| joins_next(p));
+
+        if let Some((n, ligature)) = next.filter(|_| c == LAM).and_then(|n| Some((n, lam_alef(chars[n])?))) {
+            out.extend(char::from_u32(ligature + from_prev as u32));
+            out.extend(&chars[i + 1..n]);
+            i = n + 1;
+            continue;
+        }
+
+        let to_next = joining == Joining::Dual && next.is_some_and(|n| joins_previous(chars[n]));
+        let offset = match (from_prev, to_next) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        out.extend(char::from_u32(isolated + offset));
+        i += 1;
+    }
+    out
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')', ')' => '(',
+        '[' => ']', ']' => '[',
+        '{' => '}', '}' => '{',
+        '<' => '>', '>' => '<',
+        '«' => '»', '»' => '«',
+        '‹' => '›', '›' => '‹',
+        c => c,
+    }
+}
+
+fn paragraph_level(text: &str) -> Level {
+    BidiInfo::new(text, None).paragraphs.first().map_or(Level::ltr(), |p| p.level)
+}
+
+/// One line in display order, with right-to-left runs reversed.
+fn reorder(line: &str, base: Option<Level>) -> String {
+    let info = BidiInfo::new(line, base);
+    let mut out = String::with_capacity(line.len());
+    for para in &info.paragraphs {
+        let (levels, runs) = info.visual_runs(para, para.range.clone());
+        for run in runs {
+            let text = &line[run.clone()];
+            if levels[run.start].is_rtl() {
+                // Reversed a letter at a time, each keeping its marks after it
+                let mut clusters: Vec<String> = Vec::new();
+                for c in text.chars() {
+                    match clusters.last_mut() {
+                        Some(cluster) if bidi_class(c) == BidiClass::NSM => cluster.push(c),
+                        _ => clusters.push(mirror(c).to_string()),
+                    }
+                }
+                out.extend(clusters.into_iter().rev());
+            } else {
+                out.push_str(text);
+            }
+        }
+    }
+    out
+}
+
+/// `text` shaped and in display order, for single-line text egui lays out
+/// as given. Text fields aren't covered: egui edits and draws them in
+/// logical order.
+pub fn visual(text: &str) -> String {
+    if !text.chars().any(is_rtl) {
+        return text.to_string();
+    }
+    shape(text).split('\n').map(|line| reorder(line, None)).collect::<Vec<_>>().join("\n")
+}
+
+/// `ui.label` for text that may be right to left. Lines are broken in
+/// logical order first so wrapped paragraphs read top to bottom, then each
+/// is put in display order.
+pub fn label(ui: &mut egui::Ui, text: egui::RichText) -> egui::Response {
+    if !text.text().chars().any(is_rtl) {
+        return ui.label(text);
+    }
+    let mut job = egui::WidgetText::from(text).into_layout_job(ui.style(), egui::FontSelection::Default, egui::Align::Center);
+    job.text = shape(&job.text);
+    job.sections[0].byte_range = 0..job.text.len();
+    job.wrap.max_width = ui.available_width();
+    let galley = ui.fonts(|f| f.layout_job(job.clone()));
+
+    let levels: Vec<Level> = job.text.split('\n').map(paragraph_level).collect();
+    let mut chars = job.text.chars();
+    let mut lines = Vec::new();
+    let mut para = 0;
+    for row in &galley.rows {
+        let line: String = chars.by_ref().take(row.char_count_including_newline()).collect();
+        lines.push(reorder(line.trim_end_matches('\n'), levels.get(para).copied()));
+        if row.ends_with_newline {
+            para += 1;
+        }
+    }
+    job.text = lines.join("\n");
+    job.sections[0].byte_range = 0..job.text.len();
+    ui.label(job)
+}
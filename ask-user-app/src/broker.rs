@@ -0,0 +1,379 @@
+use crate::window::{MonitorChoice, Placement, Position};
+use crate::{App, Appearance, Dialog, DraftStore, Event, InputData, Outcome, Theme};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+/// What a caller hands the window that is already open: its input, plus
+/// how it would have opened a window of its own.
+#[derive(Serialize, Deserialize)]
+pub struct Request {
+    pub input: InputData,
+    pub appearance: Appearance,
+    pub position: Option<Position>,
+    pub monitor: Option<MonitorChoice>,
+    /// The caller's working directory; the window's process has its own.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+}
+
+impl Request {
+    /// Names the caller in the tab strip.
+    fn label(&self) -> String {
+        let input = &self.input;
+        let requester = &input.requester;
+        requester.source.agent.clone()
+            .or_else(|| requester.title.clone())
+            .or_else(|| requester.source.session.clone())
+            .or_else(|| input.questions.first().map(|q| q.header.clone()).filter(|h| !h.is_empty()))
+            .or_else(|| input.confirm.as_ref().map(|c| c.title.clone()))
+            .unwrap_or_else(|| "Question".to_string())
+    }
+
+    fn dialog(self, ctx: &egui::Context, placement: &Placement, tx: mpsc::Sender<Outcome>) -> App {
+        let input = self.input;
+        let app = match input.confirm {
+            Some(confirm) => App::confirm(confirm, tx).with_requester(input.requester),
+            None => {
+                let drafts = DraftStore::for_questions(&input.questions);
+                App::new(input.questions, tx).with_review(input.review).with_requester(input.requester).with_drafts(drafts)
+            }
+        };
+        let mut app = app.with_placement(placement.clone()).with_appearance(ctx, &self.appearance).with_cwd(self.cwd);
+        app.queued = true;
+        app
+    }
+}
+
+/// Where a queued dialog's outcome goes.
+pub trait Reply {
+    fn answer(self, outcome: Outcome);
+}
+
+struct Tab<R> {
+    label: String,
+    app: App,
+    rx: mpsc::Receiver<Outcome>,
+    reply: R,
+}
+
+/// Several callers' dialogs sharing one window, one tab each; each caller
+/// gets only its own outcome.
+pub struct Queue<R> {
+    tabs: Vec<Tab<R>>,
+    active: usize,
+    incoming: mpsc::Receiver<(Request, R)>,
+    /// Where the window opened; new tabs fit the window the same way.
+    placement: Placement,
+    /// Another tab is shown than last frame.
+    switched: bool,
+}
+
+impl<R: Reply> Queue<R> {
+    pub fn new(
+        ctx: &egui::Context,
+        first: (Request, R),
+        placement: Placement,
+        incoming: mpsc::Receiver<(Request, R)>,
+    ) -> Self {
+        let mut queue = Self { tabs: Vec::new(), active: 0, incoming, placement, switched: false };
+        queue.add(ctx, first);
+        queue
+    }
+
+    fn add(&mut self, ctx: &egui::Context, (request, reply): (Request, R)) {
+        let (tx, rx) = mpsc::channel();
+        let label = request.label();
+        // Later callers' fonts wait until their tab is shown, and the window
+        // keeps the zoom the user has picked
+        let scratch = egui::Context::default();
+        let app = request.dialog(if self.tabs.is_empty() { ctx } else { &scratch }, &self.placement, tx);
+        self.tabs.push(Tab { label, app, rx, reply });
+    }
+
+    /// Hands finished dialogs their outcome and drops their tabs.
+    fn finish(&mut self) {
+        let mut i = 0;
+        while i < self.tabs.len() {
+            let Ok(outcome) = self.tabs[i].rx.try_recv() else {
+                i += 1;
+                continue;
+            };
+            let tab = self.tabs.remove(i);
+            tab.reply.answer(outcome);
+            if self.active > i || self.active == self.tabs.len() {
+                self.active = self.active.saturating_sub(1);
+            }
+            self.switched = true;
+        }
+    }
+}
+
+impl<R: Reply> Dialog for Queue<R> {
+    fn frame(&mut self, ctx: &egui::Context) {
+        while let Ok(request) = self.incoming.try_recv() {
+            self.add(ctx, request);
+        }
+
+        // Closing the window ends every dialog in it
+        if ctx.input(|i| i.viewport().close_requested()) {
+            for tab in &mut self.tabs {
+                tab.app.apply(Event::Close);
+            }
+        }
+
+        let labels: Vec<String> = self.tabs.iter().map(|t| t.label.clone()).collect();
+        let active = self.active;
+        let mut picked = None;
+        if let Some(tab) = self.tabs.get_mut(active) {
+            if self.switched {
+                // The window was sized and titled for another tab
+                tab.app.window_size = None;
+                tab.app.titled = false;
+                self.switched = false;
+            }
+            tab.app.show(ctx, |ui, theme| {
+                if labels.len() > 1 {
+                    picked = tab_strip(ui, theme, &labels, active);
+                }
+            });
+        }
+        if let Some(i) = picked.filter(|&i| i != active) {
+            self.active = i;
+            self.switched = true;
+            ctx.request_repaint();
+        }
+
+        if self.tabs.iter().all(|t| t.app.closing) {
+            if let Some(tab) = self.tabs.first_mut() {
+                tab.app.remember_geometry(ctx);
+            }
+        }
+        self.finish();
+        if self.tabs.is_empty() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        } else if self.switched {
+            // Fonts change from the next frame, the one that shows the tab
+            if let Some(fonts) = &self.tabs[self.active].app.fonts {
+                fonts.reinstall(ctx);
+            }
+            ctx.request_repaint();
+        }
+    }
+
+    fn animating(&self) -> bool {
+        self.tabs.get(self.active).is_some_and(|t| t.app.animating())
+    }
+}
+
+impl<R: Reply> eframe::App for Queue<R> {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        Dialog::frame(self, ctx);
+    }
+}
+
+/// One pill per waiting caller; returns the one clicked.
+fn tab_strip(ui: &mut egui::Ui, theme: &Theme, labels: &[String], active: usize) -> Option<usize> {
+    let mut picked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(6.0, 6.0);
+        for (i, label) in labels.iter().enumerate() {
+            let (fill, color) = if i == active {
+                (theme.accent_muted, theme.text_primary)
+            } else {
+                (theme.surface, theme.text_muted)
+            };
+            let pill = egui::Frame::new()
+                .fill(fill)
+                .stroke(egui::Stroke::new(1.0, if i == active { theme.accent } else { theme.border_subtle }))
+                .corner_radius(6)
+                .inner_margin(egui::Margin::symmetric(10, 4))
+                .show(ui, |ui| {
+                    crate::bidi::label(ui, egui::RichText::new(label).color(color).size(theme.size_caption));
+                });
+            let click = ui.interact(pill.response.rect, ui.id().with(("tab", i)), egui::Sense::click());
+            if click.clicked() {
+                picked = Some(i);
+            }
+        }
+    });
+    ui.add_space(16.0);
+    picked
+}
+
+#[cfg(unix)]
+pub use unix::{ask, serve};
+
+#[cfg(not(unix))]
+pub fn ask(_: &Request) -> Option<Outcome> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn serve(_: Option<crate::Renderer>) -> eframe::Result<()> {
+    eprintln!("Error: queueing dialogs into one window needs Unix sockets");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{Queue, Reply, Request};
+    use crate::window::Placement;
+    use crate::{paths, Outcome, Renderer};
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::DirBuilderExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use zeroize::Zeroize;
+
+    /// How long to wait for a freshly started window, or for the caller
+    /// that started it to connect.
+    const STARTUP: Duration = Duration::from_secs(5);
+    const POLL: Duration = Duration::from_millis(20);
+
+    fn socket_path() -> Option<PathBuf> {
+        let dir = paths::runtime_dir()?;
+        // Only this user may connect
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir).ok()?;
+        Some(dir.join("queue.sock"))
+    }
+
+    impl Reply for UnixStream {
+        fn answer(mut self, outcome: Outcome) {
+            if let Ok(mut line) = serde_json::to_vec(&outcome) {
+                line.push(b'\n');
+                let _ = self.write_all(&line);
+                line.zeroize();
+            }
+        }
+    }
+
+    /// Puts `request` into the shared window, starting one if none is
+    /// open, and waits for its outcome. `None` when that can't be done;
+    /// the caller then opens a window of its own.
+    pub fn ask(request: &Request) -> Option<Outcome> {
+        let path = socket_path()?;
+        let stream = match UnixStream::connect(&path) {
+            Ok(stream) => stream,
+            Err(_) => {
+                start()?;
+                let deadline = Instant::now() + STARTUP;
+                loop {
+                    match UnixStream::connect(&path) {
+                        Ok(stream) => break stream,
+                        Err(_) if Instant::now() < deadline => thread::sleep(POLL),
+                        Err(_) => return None,
+                    }
+                }
+            }
+        };
+
+        let mut line = serde_json::to_vec(request).ok()?;
+        line.push(b'\n');
+        (&stream).write_all(&line).ok()?;
+        let mut reply = String::new();
+        BufReader::new(&stream).read_line(&mut reply).ok()?;
+        // Nothing back means the window went away before answering
+        let outcome = serde_json::from_str(&reply).ok();
+        reply.zeroize();
+        outcome
+    }
+
+    /// Starts `serve` detached from the caller, so the window outlives
+    /// whichever caller opened it.
+    fn start() -> Option<()> {
+        let exe = std::env::current_exe().ok()?;
+        let log = paths::state_dir().and_then(|d| {
+            fs::create_dir_all(&d).ok()?;
+            fs::File::create(d.join("queue.log")).ok()
+        });
+        Command::new(exe)
+            .arg("serve")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log.map(Stdio::from).unwrap_or_else(Stdio::null))
+            .spawn()
+            .ok()
+            .map(|_| ())
+    }
+
+    fn read_request(stream: &UnixStream) -> Option<Request> {
+        // A caller that connects and says nothing mustn't hold up the rest
+        stream.set_read_timeout(Some(STARTUP)).ok()?;
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).ok()?;
+        serde_json::from_str(&line).ok()
+    }
+
+    /// Listens for callers and shows their dialogs in one window until the
+    /// last is answered.
+    pub fn serve(renderer: Option<Renderer>) -> eframe::Result<()> {
+        let Some(path) = socket_path() else { return Ok(()) };
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            // Left behind by a window that crashed
+            Err(_) if UnixStream::connect(&path).is_err() => {
+                let _ = fs::remove_file(&path);
+                match UnixListener::bind(&path) {
+                    Ok(listener) => listener,
+                    Err(_) => return Ok(()),
+                }
+            }
+            // Another window got there first
+            Err(_) => return Ok(()),
+        };
+
+        // The caller that started us connects straight away
+        let _ = listener.set_nonblocking(true);
+        let deadline = Instant::now() + STARTUP;
+        let first = loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let _ = stream.set_nonblocking(false);
+                    if let Some(request) = read_request(&stream) {
+                        break (request, stream);
+                    }
+                }
+                Err(_) if Instant::now() < deadline => thread::sleep(POLL),
+                Err(_) => {
+                    let _ = fs::remove_file(&path);
+                    return Ok(());
+                }
+            }
+        };
+        let _ = listener.set_nonblocking(false);
+
+        let (request, _) = &first;
+        let placement = Placement::new(request.position, request.monitor);
+        let renderer = renderer.unwrap_or(request.appearance.renderer);
+        let viewport = crate::viewport(&placement);
+        let centered = placement.position.is_none();
+        let result = crate::open_window(viewport, centered, renderer, move |ctx| {
+            let (tx, incoming) = mpsc::channel();
+            let ctx_ = ctx.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let (tx, ctx) = (tx.clone(), ctx_.clone());
+                    thread::spawn(move || {
+                        if let Some(request) = read_request(&stream) {
+                            if tx.send((request, stream)).is_ok() {
+                                ctx.request_repaint();
+                            }
+                        }
+                    });
+                }
+            });
+            Queue::new(ctx, first, placement, incoming)
+        });
+        // Anyone who connects from here on gets no answer and opens their
+        // own window
+        let _ = fs::remove_file(&path);
+        result
+    }
+}
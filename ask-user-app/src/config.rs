@@ -0,0 +1,93 @@
+use crate::paths;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// User configuration read from `config.json` in the config dir. Every
+/// field is optional; command-line flags take precedence.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    pub audit: AuditConfig,
+    /// Zoom factor for the whole dialog; `--scale` overrides it.
+    pub scale: Option<f32>,
+    /// Font files used ahead of the bundled fonts.
+    pub fonts: Vec<PathBuf>,
+    /// Font files tried after the bundled fonts, before system fallbacks.
+    pub fallback_fonts: Vec<PathBuf>,
+    /// Same as `--reduced-motion`.
+    pub reduced_motion: bool,
+    /// `--renderer` overrides it.
+    pub renderer: Option<crate::Renderer>,
+    /// `--theme` overrides it.
+    pub theme: Option<crate::ColorScheme>,
+    /// Why the `audit` settings couldn't be read; they're left at their
+    /// defaults, which is only an error once the log is wanted.
+    #[serde(skip)]
+    pub audit_error: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AuditConfig {
+    pub enabled: bool,
+    pub path: Option<PathBuf>,
+    /// Rotate the log once it would grow past this many bytes.
+    pub max_bytes: u64,
+    /// Number of rotated files to keep alongside the live log.
+    pub keep: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            max_bytes: 10 * 1024 * 1024,
+            keep: 5,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `config.json`. A setting that can't be read is left at its
+    /// default with a warning, so one typo doesn't stop every dialog.
+    pub fn load() -> Self {
+        let Some(path) = paths::config_dir().map(|d| d.join("config.json")) else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content, &path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => Self::unreadable(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse(content: &str, path: &Path) -> Self {
+        if let Ok(config) = serde_json::from_str(content) {
+            return config;
+        }
+        let mut settings: serde_json::Map<String, serde_json::Value> = match serde_json::from_str(content) {
+            Ok(settings) => settings,
+            Err(e) => return Self::unreadable(format!("{}: {}", path.display(), e)),
+        };
+        // Keep every setting that reads on its own
+        let mut audit_error = None;
+        settings.retain(|key, value| {
+            let alone = serde_json::json!({ key.as_str(): value.clone() });
+            let Err(e) = serde_json::from_value::<Self>(alone) else { return true };
+            eprintln!("Warning: ignoring {:?} in {}: {}", key, path.display(), e);
+            if key == "audit" {
+                audit_error = Some(format!("{}: audit: {}", path.display(), e));
+            }
+            false
+        });
+        let config = serde_json::from_value(settings.into()).unwrap_or_default();
+        Self { audit_error, ..config }
+    }
+
+    fn unreadable(error: String) -> Self {
+        eprintln!("Warning: ignoring {}", error);
+        Self { audit_error: Some(error), ..Self::default() }
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// A yes/no prompt for a single action, shown instead of questions.
+#[derive(clap::Args, Serialize, Deserialize, Debug, Clone)]
+#[group(skip)]
+pub struct Confirm {
+    /// What is being asked, e.g. "Delete the build directory?"
+    #[arg(long)]
+    pub title: String,
+
+    /// Longer explanation shown under the title
+    #[arg(long, default_value = "", hide_default_value = true)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+
+    /// Command shown verbatim in a preview box
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Unified diff shown in a preview box
+    #[arg(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+
+    /// Style the prompt as destructive; Enter alone won't approve it
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::is_false")]
+    pub danger: bool,
+
+    /// Label of the approve button
+    #[arg(long, value_name = "LABEL")]
+    #[serde(default, rename = "confirmLabel", skip_serializing_if = "Option::is_none")]
+    pub confirm_label: Option<String>,
+
+    /// Label of the deny button
+    #[arg(long, value_name = "LABEL")]
+    #[serde(default, rename = "denyLabel", skip_serializing_if = "Option::is_none")]
+    pub deny_label: Option<String>,
+
+    /// Only enable the approve button once this word has been typed
+    #[arg(long, value_name = "WORD")]
+    #[serde(default, rename = "typeToConfirm", skip_serializing_if = "Option::is_none")]
+    pub type_to_confirm: Option<String>,
+}
+
+impl Confirm {
+    pub fn confirm_label(&self) -> &str {
+        self.confirm_label.as_deref().unwrap_or("Approve")
+    }
+
+    pub fn deny_label(&self) -> &str {
+        self.deny_label.as_deref().unwrap_or("Deny")
+    }
+
+    /// Whether `typed` unlocks the approve button.
+    pub fn unlocked(&self, typed: &str) -> bool {
+        self.type_to_confirm.as_deref().is_none_or(|word| typed.trim() == word)
+    }
+}
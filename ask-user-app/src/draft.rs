@@ -0,0 +1,77 @@
+use crate::{paths, AnswerData, Question};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Drafts older than this are pruned rather than offered for restore.
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Snapshot of an in-progress dialog.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Draft {
+    pub answers: Vec<AnswerData>,
+    /// Index of the question on screen; equal to the question count when
+    /// the user had reached the review screen.
+    pub screen: usize,
+    pub selected: Vec<bool>,
+    pub custom: String,
+    #[serde(default)]
+    pub other: bool,
+    pub remember: bool,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// The on-disk slot for drafts of one particular question set.
+pub struct DraftStore {
+    path: PathBuf,
+}
+
+impl DraftStore {
+    pub fn for_questions(questions: &[Question]) -> Option<Self> {
+        let dir = paths::state_dir()?.join("drafts");
+        prune(&dir);
+        let json = serde_json::to_string(questions).ok()?;
+        Some(Self { path: dir.join(format!("{:016x}.json", fnv1a(json.as_bytes()))) })
+    }
+
+    pub fn load(&self) -> Option<Draft> {
+        serde_json::from_str(&fs::read_to_string(&self.path).ok()?).ok()
+    }
+
+    pub fn save(&self, draft: &Draft) {
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string(draft) {
+            let tmp = self.path.with_extension("json.tmp");
+            if fs::write(&tmp, json).is_ok() {
+                let _ = fs::rename(tmp, &self.path);
+            }
+        }
+    }
+
+    pub fn discard(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let stale = entry.metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .is_some_and(|age| age > MAX_AGE);
+        if stale {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,162 @@
+use eframe::egui;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// System fonts tried, in order, for CJK text. egui's bundled fonts only
+/// cover Latin, Greek and Cyrillic.
+#[cfg(target_os = "linux")]
+const CJK: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+];
+#[cfg(target_os = "macos")]
+const CJK: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+];
+#[cfg(windows)]
+const CJK: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\YuGothR.ttc",
+    "C:\\Windows\\Fonts\\malgun.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+const CJK: &[&str] = &[];
+
+/// Fonts with Hebrew and the Arabic presentation forms the dialog shapes
+/// Arabic text into.
+#[cfg(target_os = "linux")]
+const RTL: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf",
+];
+#[cfg(target_os = "macos")]
+const RTL: &[&str] = &[
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+];
+#[cfg(windows)]
+const RTL: &[&str] = &["C:\\Windows\\Fonts\\arial.ttf", "C:\\Windows\\Fonts\\segoeui.ttf"];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+const RTL: &[&str] = &[];
+
+/// Outline emoji fonts for symbols newer than the bundled Noto Emoji.
+/// Color bitmap fonts (Noto Color Emoji, Apple Color Emoji) can't be drawn.
+#[cfg(target_os = "linux")]
+const EMOJI: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+    "/usr/share/fonts/TTF/Symbola.ttf",
+];
+#[cfg(target_os = "macos")]
+const EMOJI: &[&str] = &["/System/Library/Fonts/Apple Symbols.ttf"];
+#[cfg(windows)]
+const EMOJI: &[&str] = &["C:\\Windows\\Fonts\\seguiemj.ttf", "C:\\Windows\\Fonts\\seguisym.ttf"];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+const EMOJI: &[&str] = &[];
+
+/// The dialog's font set: the user's fonts, then egui's bundled ones, then
+/// the user's fallbacks, then system fallbacks once some text needs them.
+pub struct Fonts {
+    definitions: egui::FontDefinitions,
+    /// Whether the set differs from egui's default.
+    custom: bool,
+    /// System fallbacks were already looked for, or aren't wanted.
+    system_tried: bool,
+}
+
+impl Fonts {
+    pub fn new(primary: &[impl AsRef<Path>], fallback: &[impl AsRef<Path>], system: bool) -> Self {
+        let mut fonts = Self {
+            definitions: egui::FontDefinitions::default(),
+            custom: false,
+            system_tried: !system,
+        };
+        for (i, path) in primary.iter().enumerate() {
+            match load(path.as_ref()) {
+                Ok(data) => fonts.add(format!("user-{}", i), data, Some(i)),
+                Err(e) => eprintln!("Warning: could not load font {}: {}", path.as_ref().display(), e),
+            }
+        }
+        for (i, path) in fallback.iter().enumerate() {
+            match load(path.as_ref()) {
+                Ok(data) => fonts.add(format!("fallback-{}", i), data, None),
+                Err(e) => eprintln!("Warning: could not load font {}: {}", path.as_ref().display(), e),
+            }
+        }
+        fonts
+    }
+
+    /// Adds a font in front of the bundled ones (`at`) or after every font
+    /// so far. Monospace text only ever falls back to it.
+    fn add(&mut self, name: String, data: egui::FontData, at: Option<usize>) {
+        self.definitions.font_data.insert(name.clone(), Arc::new(data));
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            let list = self.definitions.families.entry(family.clone()).or_default();
+            match at {
+                Some(i) if family == egui::FontFamily::Proportional => list.insert(i, name.clone()),
+                _ => list.push(name.clone()),
+            }
+        }
+        self.custom = true;
+    }
+
+    /// Hands the font set to egui if it isn't just the default.
+    pub fn install(&self, ctx: &egui::Context) {
+        if self.custom {
+            ctx.set_fonts(self.definitions.clone());
+        }
+    }
+
+    /// Puts this set back after another dialog in the same window
+    /// installed its own.
+    pub fn reinstall(&self, ctx: &egui::Context) {
+        ctx.set_fonts(self.definitions.clone());
+    }
+
+    /// Loads the system CJK, Arabic/Hebrew and emoji fallbacks the first time `text` has a
+    /// character none of the current fonts can draw.
+    pub fn cover(&mut self, ctx: &egui::Context, text: impl FnOnce() -> String) {
+        if self.system_tried {
+            return;
+        }
+        let text = text();
+        let font = egui::FontId::proportional(12.0);
+        let missing = ctx.fonts(|f| {
+            text.chars().any(|c| !c.is_whitespace() && !c.is_control() && !f.has_glyph(&font, c))
+        });
+        if !missing {
+            return;
+        }
+        self.system_tried = true;
+        let mut added = false;
+        for (name, candidates) in [("system-cjk", CJK), ("system-rtl", RTL), ("system-emoji", EMOJI)] {
+            if let Some(data) = candidates.iter().find_map(|p| load(Path::new(p)).ok()) {
+                self.add(name.to_string(), data, None);
+                added = true;
+            }
+        }
+        if added {
+            self.install(ctx);
+        }
+    }
+}
+
+/// Reads a font file, checking it parses; egui panics on fonts it can't.
+fn load(path: &Path) -> Result<egui::FontData, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    ab_glyph::FontRef::try_from_slice(&bytes).map_err(|e| e.to_string())?;
+    Ok(egui::FontData::from_owned(bytes))
+}
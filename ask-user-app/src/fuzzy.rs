@@ -0,0 +1,45 @@
+/// Scores `text` against a fuzzy `pattern`: every non-space character of
+/// the pattern must appear in `text` in order, ignoring case. Higher is
+/// better; consecutive runs and matches at word starts score extra, skipped
+/// characters cost a little. `None` if the pattern doesn't match.
+pub fn score(pattern: &str, text: &str) -> Option<i32> {
+    let pattern: Vec<char> = pattern.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut prev: Option<char> = None;
+    let mut prev_matched = false;
+
+    for c in text.chars() {
+        if matched == pattern.len() {
+            break;
+        }
+        if c.to_lowercase().eq(std::iter::once(pattern[matched])) {
+            score += 1;
+            if prev_matched {
+                score += 5;
+            }
+            let word_start = prev.is_none_or(|p| !p.is_alphanumeric())
+                || (c.is_uppercase() && prev.is_some_and(char::is_lowercase));
+            if word_start {
+                score += 8;
+            }
+            matched += 1;
+            prev_matched = true;
+        } else {
+            if matched > 0 {
+                score -= 1;
+            }
+            prev_matched = false;
+        }
+        prev = Some(c);
+    }
+
+    (matched == pattern.len()).then_some(score)
+}
@@ -0,0 +1,143 @@
+use base64::Engine;
+use eframe::egui;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Largest image file or decoded data accepted.
+const MAX_BYTES: u64 = 10 << 20;
+/// Largest width or height accepted, in pixels.
+const MAX_SIDE: u32 = 4096;
+/// Longer side SVGs are drawn at, so they stay sharp when zoomed.
+#[cfg(feature = "svg")]
+const SVG_SIDE: f32 = 1024.0;
+
+/// An image from the input, ready to draw or with why it can't be.
+pub enum Image {
+    Ready(egui::TextureHandle),
+    Failed(&'static str),
+}
+
+/// Images referenced by the questions, each loaded the first time it's
+/// shown.
+#[derive(Default)]
+pub struct Images {
+    loaded: HashMap<String, Image>,
+    /// What relative file paths are read from, if not the working
+    /// directory.
+    pub dir: Option<PathBuf>,
+}
+
+impl Images {
+    /// The image `source` names: a file path, a `data:` URI or bare base64.
+    pub fn get(&mut self, ctx: &egui::Context, source: &str) -> &Image {
+        let name = format!("image-{}", self.loaded.len());
+        let dir = self.dir.as_deref();
+        self.loaded.entry(source.to_string()).or_insert_with(|| match bytes(source, dir).and_then(|b| decode(&b)) {
+            Ok(image) => Image::Ready(ctx.load_texture(name, image, egui::TextureOptions::LINEAR)),
+            Err(reason) => Image::Failed(reason),
+        })
+    }
+}
+
+fn bytes(source: &str, dir: Option<&Path>) -> Result<Vec<u8>, &'static str> {
+    let base64 = |data: &str| {
+        if data.len() as u64 > MAX_BYTES * 4 / 3 {
+            return Err("too large");
+        }
+        let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+        base64::engine::general_purpose::STANDARD.decode(data).map_err(|_| "unreadable")
+    };
+
+    if let Some(uri) = source.strip_prefix("data:") {
+        return match uri.split_once(',') {
+            Some((meta, data)) if meta.ends_with(";base64") => base64(data),
+            _ => Err("unsupported"),
+        };
+    }
+    let path = dir.map_or_else(|| PathBuf::from(source), |dir| dir.join(source));
+    match fs::metadata(&path) {
+        Ok(meta) if meta.len() > MAX_BYTES => Err("too large"),
+        Ok(_) => fs::read(&path).map_err(|_| "unreadable"),
+        // Long enough not to be a file name: base64 without the data: prefix
+        Err(_) if source.len() > 128 => base64(source).map_err(|_| "not found"),
+        Err(_) => Err("not found"),
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<egui::ColorImage, &'static str> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    if bytes.starts_with(b"\x89PNG") {
+        png(bytes)
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        jpeg(bytes)
+    } else if head.contains("<svg") {
+        svg(bytes)
+    } else {
+        Err("unsupported")
+    }
+}
+
+fn check_size(width: u32, height: u32) -> Result<[usize; 2], &'static str> {
+    if width == 0 || height == 0 {
+        Err("unreadable")
+    } else if width > MAX_SIDE || height > MAX_SIDE {
+        Err("too large")
+    } else {
+        Ok([width as usize, height as usize])
+    }
+}
+
+fn png(bytes: &[u8]) -> Result<egui::ColorImage, &'static str> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|_| "unreadable")?;
+    let size = check_size(reader.info().width, reader.info().height)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|_| "unreadable")?;
+    let data = &buf[..info.buffer_size()];
+    let rgba: Vec<u8> = match info.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => data.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("unsupported"),
+    };
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, &rgba))
+}
+
+fn jpeg(bytes: &[u8]) -> Result<egui::ColorImage, &'static str> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    decoder.read_info().map_err(|_| "unreadable")?;
+    let info = decoder.info().ok_or("unreadable")?;
+    let size = check_size(info.width as u32, info.height as u32)?;
+    let data = decoder.decode().map_err(|_| "unreadable")?;
+    match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => Ok(egui::ColorImage::from_rgb(size, &data)),
+        jpeg_decoder::PixelFormat::L8 => Ok(egui::ColorImage::from_gray(size, &data)),
+        _ => Err("unsupported"),
+    }
+}
+
+#[cfg(feature = "svg")]
+fn svg(bytes: &[u8]) -> Result<egui::ColorImage, &'static str> {
+    use resvg::{tiny_skia, usvg};
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default()).map_err(|_| "unreadable")?;
+    let size = tree.size();
+    let scale = SVG_SIDE / size.width().max(size.height());
+    let (width, height) = ((size.width() * scale).ceil() as u32, (size.height() * scale).ceil() as u32);
+    let mut pixmap = tiny_skia::Pixmap::new(width.max(1), height.max(1)).ok_or("unreadable")?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    Ok(egui::ColorImage::from_rgba_premultiplied([pixmap.width() as usize, pixmap.height() as usize], pixmap.data()))
+}
+
+#[cfg(not(feature = "svg"))]
+fn svg(_: &[u8]) -> Result<egui::ColorImage, &'static str> {
+    Err("unsupported")
+}
+
+/// The size `texture` is drawn at to fit within `max`, never enlarged.
+pub fn fit(texture: &egui::TextureHandle, max: egui::Vec2) -> egui::Vec2 {
+    let size = texture.size_vec2();
+    size * (max.x / size.x).min(max.y / size.y).min(1.0)
+}
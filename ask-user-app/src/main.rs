@@ -1,64 +1,931 @@
-use clap::Parser;
+mod audit;
+mod bidi;
+mod broker;
+mod config;
+mod confirm;
+mod draft;
+mod fonts;
+mod fuzzy;
+mod images;
+mod markdown;
+mod memory;
+mod paths;
+mod pathpick;
+mod raster;
+mod record;
+mod render;
+mod requester;
+#[cfg(feature = "software")]
+mod software;
+#[cfg(test)]
+mod tests;
+mod when;
+mod window;
+
+use audit::{AuditEntry, AuditLog};
+use bidi::Direction;
+use clap::{Parser, Subcommand};
+use config::Config;
+use confirm::Confirm;
+use draft::{Draft, DraftStore};
 use eframe::egui;
+use fonts::Fonts;
+use memory::Store;
+use record::{Recorder, Trace};
+use requester::Requester;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fs;
-use std::sync::mpsc;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::{mpsc, OnceLock};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use when::When;
+use window::{MonitorChoice, Placement, Position};
+use zeroize::Zeroize;
+
+/// How often in-progress answers are written to the draft store.
+const DRAFT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Questions with more options than this get a filterable compact list
+/// instead of option cards.
+const LONG_LIST: usize = 6;
+const LIST_ROW_HEIGHT: f32 = 30.0;
+/// Largest size of an option's picture in its card.
+const THUMBNAIL: egui::Vec2 = egui::vec2(72.0, 54.0);
+/// Stands in for a secret answer on the review screen, whatever its length.
+const SECRET_MASK: &str = "••••••••";
+/// What recordings hold in place of a secret's text.
+const SECRET_PLACEHOLDER: &str = "<secret>";
+const NUMBER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1, egui::Key::Num2, egui::Key::Num3,
+    egui::Key::Num4, egui::Key::Num5, egui::Key::Num6,
+    egui::Key::Num7, egui::Key::Num8, egui::Key::Num9,
+];
+/// Zoom factors reachable with `--scale` and Ctrl/Cmd +/-.
+const SCALE_RANGE: RangeInclusive<f32> = 0.5..=3.0;
+const SCALE_STEP: f32 = 0.1;
 
 #[derive(Parser, Debug)]
-#[command(name = "ask-user")]
+#[command(name = "ask-user", subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(short, long)]
-    input: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(short, long, required = true)]
+    input: Option<String>,
+
+    /// Write a JSONL trace of the session (input, events, response) to this path
+    #[arg(long, value_name = "PATH")]
+    record: Option<String>,
+
+    /// Append this session to the audit log in the state dir
+    #[arg(long)]
+    audit: bool,
+
+    /// Append this session to the audit log at PATH (implies --audit)
+    #[arg(long, value_name = "PATH")]
+    audit_log: Option<String>,
+
+    /// Where the window opens: center, cursor, top-right, or X,Y from the
+    /// display's top-left corner. Defaults to where it was last left
+    #[arg(long, value_name = "WHERE")]
+    position: Option<Position>,
+
+    /// Display to open on: primary, or a number starting at 1. Defaults to
+    /// the one under the mouse
+    #[arg(long, value_name = "DISPLAY")]
+    monitor: Option<MonitorChoice>,
+
+    /// Zoom factor for the whole dialog, e.g. 1.25. Ctrl/Cmd with +, - and 0
+    /// change it while the window is open
+    #[arg(long, value_name = "FACTOR", value_parser = parse_scale, global = true)]
+    scale: Option<f32>,
+
+    /// Font file to draw text with ahead of the bundled fonts; repeatable
+    #[arg(long = "font", value_name = "PATH", global = true)]
+    fonts: Vec<PathBuf>,
+
+    /// Layout direction; overrides `direction` in the input
+    #[arg(long, value_enum, global = true)]
+    direction: Option<Direction>,
+
+    /// Color theme
+    #[arg(long, value_enum, global = true)]
+    theme: Option<ColorScheme>,
+
+    /// Show every change at once instead of animating it
+    #[arg(long)]
+    reduced_motion: bool,
+
+    /// How the window is drawn. `auto` uses OpenGL and falls back to the
+    /// CPU when OpenGL can't be set up
+    #[arg(long, value_enum)]
+    renderer: Option<Renderer>,
+
+    /// Always open a window of our own instead of joining the one another
+    /// caller already has open
+    #[arg(long)]
+    no_queue: bool,
+}
+
+/// Colors the dialog is drawn in (`--theme`).
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ColorScheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// What draws the window (`--renderer`).
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Renderer {
+    #[default]
+    Auto,
+    Gl,
+    Software,
+}
+
+fn parse_scale(s: &str) -> Result<f32, String> {
+    s.parse().ok().filter(|f| SCALE_RANGE.contains(f)).ok_or_else(|| {
+        format!("expected a number from {} to {}", SCALE_RANGE.start(), SCALE_RANGE.end())
+    })
+}
+
+/// How the dialog is drawn, from flags and `config.json`.
+#[derive(Serialize, Deserialize)]
+struct Appearance {
+    scale: f32,
+    fonts: Vec<PathBuf>,
+    fallback_fonts: Vec<PathBuf>,
+    /// Whether to reach for system fonts when text needs them.
+    system_fonts: bool,
+    direction: Direction,
+    reduced_motion: bool,
+    renderer: Renderer,
+    theme: ColorScheme,
+}
+
+impl Appearance {
+    fn new(args: &Args, config: &Config, input: &InputData) -> Self {
+        let scale = args.scale.or(config.scale).unwrap_or(1.0);
+        Self {
+            scale: scale.clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end()),
+            fonts: args.fonts.iter().chain(&config.fonts).cloned().collect(),
+            fallback_fonts: config.fallback_fonts.clone(),
+            system_fonts: true,
+            direction: args.direction.unwrap_or(input.direction),
+            reduced_motion: args.reduced_motion || config.reduced_motion,
+            renderer: args.renderer.or(config.renderer).unwrap_or_default(),
+            theme: args.theme.or(config.theme).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a trace written by --record and print the resulting response
+    Replay {
+        trace: String,
+
+        /// Apply the events without opening a window
+        #[arg(long)]
+        headless: bool,
+    },
+
+    /// Ask to approve or deny a single action and print the decision
+    Confirm {
+        #[command(flatten)]
+        confirm: Confirm,
+
+        /// Show the unified diff in this file ("-" for stdin) as a preview
+        #[arg(long, value_name = "PATH")]
+        diff: Option<String>,
+    },
+
+    /// Draw a dialog offscreen and save it as a PNG
+    Render {
+        /// Input JSON, as for --input
+        input: String,
+
+        /// Where to write the PNG
+        #[arg(short, long, value_name = "PATH")]
+        output: PathBuf,
+
+        /// Screen to draw: a question number starting at 1, or review
+        #[arg(long, default_value = "1")]
+        screen: render::Shown,
+
+        /// Width in points
+        #[arg(long, default_value_t = window::DEFAULT_SIZE.x)]
+        width: f32,
+
+        /// Height in points. Defaults to fitting the content, as the window
+        /// does
+        #[arg(long)]
+        height: Option<f32>,
+    },
+
+    /// Show dialogs from every caller in one window (started as needed)
+    #[command(hide = true)]
+    Serve,
+
+    /// Forget remembered answers for the current project
+    Forget {
+        /// The rememberKey values to forget
+        #[arg(required_unless_present = "all")]
+        keys: Vec<String>,
+
+        /// Forget every remembered answer, in every project
+        #[arg(long, conflicts_with = "keys")]
+        all: bool,
+    },
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct QuestionOption {
     label: String,
     #[serde(default)]
     description: String,
+    /// Stable identifier returned alongside the label.
+    #[serde(default, alias = "id", skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// Shown with a "Recommended" badge.
+    #[serde(default, skip_serializing_if = "is_false")]
+    recommended: bool,
+    /// Preselected when the question is shown.
+    #[serde(default, skip_serializing_if = "is_false")]
+    default: bool,
+    /// Section heading this option is listed under in long lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    /// Picture shown in the option's card: a file path or base64 data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+}
+
+impl QuestionOption {
+    /// Models were told to append "(Recommended)" to labels before the flag
+    /// existed; turn that suffix into the flag so it doesn't leak into answers.
+    fn normalize(&mut self) {
+        if let Some(label) = self.label.trim_end().strip_suffix("(Recommended)") {
+            self.label = label.trim_end().to_string();
+            self.recommended = true;
+        }
+    }
+
+    /// Matrix rows: what the row's answer is keyed by, its `value` or else
+    /// its label.
+    fn key(&self) -> &str {
+        self.value.as_deref().unwrap_or(&self.label)
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Question {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     question: String,
     #[serde(default)]
     header: String,
+    #[serde(default, rename = "type", skip_serializing_if = "QuestionKind::is_choice")]
+    kind: QuestionKind,
+    /// The choices; in matrix questions, the grid's columns.
+    #[serde(default, alias = "columns")]
     options: Vec<QuestionOption>,
+    /// Matrix questions: the items each answered with `options`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rows: Vec<QuestionOption>,
     #[serde(default, rename = "multiSelect")]
     multi_select: bool,
+    /// Offer "Remember my answer"; later questions with the same key are
+    /// answered from the store without asking.
+    #[serde(default, rename = "rememberKey", skip_serializing_if = "Option::is_none")]
+    remember_key: Option<String>,
+    /// Single-select: move on as soon as an option is clicked or its
+    /// number key pressed.
+    #[serde(default, rename = "submitOnSelect", skip_serializing_if = "is_false")]
+    submit_on_select: bool,
+    /// Offer the free-text "Other" choice.
+    #[serde(default = "default_true", rename = "allowOther", skip_serializing_if = "is_true")]
+    allow_other: bool,
+    /// Path questions: what kind of path to pick.
+    #[serde(default, skip_serializing_if = "PathMode::is_file")]
+    mode: PathMode,
+    /// Path questions: where browsing starts and what relative paths are
+    /// resolved against; defaults to the working directory.
+    #[serde(default, rename = "baseDir", skip_serializing_if = "Option::is_none")]
+    base_dir: Option<String>,
+    /// Path questions: glob the file name must match, e.g. `*.{toml,json}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filter: Option<String>,
+    /// Path questions: require the path to exist. Defaults to true except
+    /// in save mode.
+    #[serde(default, rename = "mustExist", skip_serializing_if = "Option::is_none")]
+    must_exist: Option<bool>,
+    /// Picture shown under the question: a file path or base64 data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    /// Date, time and duration questions: the answer filled in at first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    /// Date, time and duration questions: earliest and shortest, latest
+    /// and longest answers accepted, written like answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<String>,
+    /// Datetime questions: IANA zone answers are given in, e.g.
+    /// `Europe/Paris`; defaults to the system's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    /// The caller's working directory, for a dialog queued into another
+    /// process's window; relative paths resolve against it.
+    #[serde(skip)]
+    cwd: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum QuestionKind {
+    /// Pick from `options`.
+    #[default]
+    Choice,
+    /// Pick a file or directory.
+    Path,
+    /// Pick from `options` for each of `rows`, in a grid.
+    Matrix,
+    Date,
+    Time,
+    DateTime,
+    Duration,
+    /// A password or token, typed into a masked field and kept out of
+    /// recordings, drafts, the audit log and remembered answers.
+    Secret,
+}
+
+impl QuestionKind {
+    fn is_choice(&self) -> bool {
+        *self == Self::Choice
+    }
+
+    /// Answered with a date, time or duration (see `when`).
+    fn is_when(&self) -> bool {
+        matches!(self, Self::Date | Self::Time | Self::DateTime | Self::Duration)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum PathMode {
+    /// An existing file.
+    #[default]
+    File,
+    /// An existing directory.
+    Directory,
+    /// A file to be written; it may not exist yet.
+    Save,
+}
+
+impl PathMode {
+    fn is_file(&self) -> bool {
+        *self == Self::File
+    }
+}
+
+impl Question {
+    fn is_long_list(&self) -> bool {
+        self.options.len() > LONG_LIST || self.options.iter().any(|o| o.group.is_some())
+    }
+
+    /// Matrix questions: where `row`'s `column` is kept in the selection,
+    /// which holds the grid row by row.
+    fn cell(&self, row: usize, column: usize) -> usize {
+        row * self.options.len() + column
+    }
+
+    /// Matrix questions: a key two rows share, so one's answer would
+    /// overwrite the other's.
+    fn repeated_row_key(&self) -> Option<&str> {
+        self.rows.iter().enumerate()
+            .find(|&(r, row)| self.rows[..r].iter().any(|earlier| earlier.key() == row.key()))
+            .map(|(_, row)| row.key())
+    }
+
+    /// Stable values for an answer: each option's `value` (its label when
+    /// it has none). `None` when the question defines no ids at all, so
+    /// plain questions keep their old output.
+    fn values_for(&self, labels: &[String], indices: &[i32]) -> Option<Vec<String>> {
+        if self.id.is_none() && self.options.iter().all(|o| o.value.is_none()) {
+            return None;
+        }
+        Some(labels.iter().zip(indices).map(|(label, &i)| {
+            usize::try_from(i).ok()
+                .and_then(|i| self.options.get(i))
+                .and_then(|o| o.value.clone())
+                .unwrap_or_else(|| label.clone())
+        }).collect())
+    }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct InputData {
+    #[serde(default)]
     questions: Vec<Question>,
+    /// Ask for approval of one action instead of asking questions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confirm: Option<Confirm>,
+    /// Caller-supplied identifiers, passed through to the audit log.
+    #[serde(default, rename = "sessionId", skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    #[serde(default, rename = "agentId", skip_serializing_if = "Option::is_none")]
+    agent_id: Option<String>,
+    #[serde(default, rename = "answersFormat")]
+    answers_format: AnswersFormat,
+    #[serde(default)]
+    review: ReviewMode,
+    #[serde(default, skip_serializing_if = "is_auto")]
+    direction: Direction,
+    /// Who is asking and why (`title`, `source`, `context`, `icon`).
+    #[serde(flatten)]
+    requester: Requester,
+}
+
+impl InputData {
+    /// Fills in what the input leaves to defaults.
+    fn normalize(&mut self) {
+        self.questions.iter_mut()
+            .flat_map(|q| q.options.iter_mut())
+            .for_each(QuestionOption::normalize);
+
+        // The ids callers pass for the audit log also say who is asking
+        let source = &mut self.requester.source;
+        source.agent = source.agent.take().or_else(|| self.agent_id.clone());
+        source.session = source.session.take().or_else(|| self.session_id.clone());
+    }
+}
+
+fn is_auto(direction: &Direction) -> bool {
+    *direction == Direction::Auto
+}
+
+/// Whether the Review screen is shown after the last question.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ReviewMode {
+    #[default]
+    Always,
+    Never,
+    /// Only when more than one question is asked.
+    Auto,
 }
 
-#[derive(Serialize, Clone)]
+/// Shape of `answers` in the printed response.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum AnswersFormat {
+    /// One `QuestionAnswer` per question.
+    #[default]
+    List,
+    /// An object keyed by question id (or header, or question text) whose
+    /// values are the chosen option values.
+    Keyed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct QuestionAnswer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     question: String,
     header: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     selected: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     selected_index: Option<serde_json::Value>,
+    /// Text typed into "Other", when it was chosen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    other: Option<String>,
+    /// Option values matching `selected`, when the question defines them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    remembered: bool,
+    /// Whether the final selection is exactly the question's default
+    /// options; absent when the question has no defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    accepted_default: Option<bool>,
+    /// Path questions: the picked path, absolute and relative to `baseDir`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relative_path: Option<String>,
+    /// Free-form comment the user added alongside their choice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    /// Secret questions; `selected` and `value` are left out wherever the
+    /// response is written down.
+    #[serde(default, skip_serializing_if = "is_false")]
+    secret: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl From<&AnswerData> for QuestionAnswer {
+    fn from(a: &AnswerData) -> Self {
+        let (selected, selected_index, value) = if let Some(rows) = &a.rows {
+            // Objects keyed by row
+            let by_row = |answer: fn(&RowAnswer) -> serde_json::Value| {
+                serde_json::Value::Object(rows.iter().map(|r| (r.key.clone(), answer(r))).collect())
+            };
+            let has_values = rows.first().is_some_and(|r| r.values.is_some());
+            if a.multi {
+                (
+                    Some(by_row(|r| serde_json::json!(r.selected))),
+                    Some(by_row(|r| serde_json::json!(r.indices))),
+                    has_values.then(|| by_row(|r| serde_json::json!(r.values))),
+                )
+            } else {
+                (
+                    Some(by_row(|r| serde_json::json!(r.selected.first()))),
+                    Some(by_row(|r| serde_json::json!(r.indices.first()))),
+                    has_values.then(|| by_row(|r| serde_json::json!(r.values.as_ref().and_then(|v| v.first())))),
+                )
+            }
+        } else if a.multi {
+            (
+                Some(serde_json::json!(a.selected)),
+                Some(serde_json::json!(a.indices)),
+                a.values.as_ref().map(|v| serde_json::json!(v)),
+            )
+        } else {
+            (
+                a.selected.first().map(|s| serde_json::json!(s)),
+                a.indices.first().map(|i| serde_json::json!(i)),
+                a.values.as_ref().and_then(|v| v.first()).map(|s| serde_json::json!(s)),
+            )
+        };
+        Self {
+            id: a.id.clone(),
+            question: a.question.clone(),
+            header: a.header.clone(),
+            selected,
+            selected_index,
+            other: a.other.clone(),
+            value,
+            remembered: a.remembered,
+            accepted_default: a.accepted_default,
+            path: a.path.clone(),
+            relative_path: a.relative_path.clone(),
+            note: a.note.clone(),
+            secret: a.secret,
+        }
+    }
+}
+
+/// Wipes every string in `value`.
+fn zeroize_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => text.zeroize(),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(zeroize_json),
+        serde_json::Value::Object(map) => map.values_mut().for_each(zeroize_json),
+        _ => {}
+    }
+}
+
+impl QuestionAnswer {
+    /// Wipes the text of a secret answer.
+    fn zeroize(&mut self) {
+        if !self.secret {
+            return;
+        }
+        [&mut self.selected, &mut self.value].into_iter().flatten().for_each(zeroize_json);
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct Response {
     status: String,
     answers: Vec<QuestionAnswer>,
 }
 
-#[derive(Clone)]
+impl Response {
+    fn from_outcome(outcome: Outcome) -> Self {
+        let (status, answers) = match outcome {
+            Outcome::Selected(answers) => ("selected", answers),
+            Outcome::Partial(answers) => ("partial", answers),
+            Outcome::Cancelled => ("cancelled", Vec::new()),
+            Outcome::Approved => ("approved", Vec::new()),
+            Outcome::Denied => ("denied", Vec::new()),
+        };
+        Self {
+            status: status.into(),
+            answers: answers.iter().map(QuestionAnswer::from).collect(),
+        }
+    }
+
+    /// A copy without the text of secret answers, for recordings and the
+    /// audit log.
+    fn redacted(&self) -> Self {
+        let answers = self.answers.iter().map(|a| match a.secret {
+            // Built field by field so the secret is never copied
+            true => QuestionAnswer {
+                id: a.id.clone(),
+                question: a.question.clone(),
+                header: a.header.clone(),
+                selected: None,
+                selected_index: a.selected_index.clone(),
+                value: None,
+                other: a.other.clone(),
+                remembered: a.remembered,
+                accepted_default: a.accepted_default,
+                path: a.path.clone(),
+                relative_path: a.relative_path.clone(),
+                note: a.note.clone(),
+                secret: true,
+            },
+            false => a.clone(),
+        }).collect();
+        Self { status: self.status.clone(), answers }
+    }
+
+    /// Keys for the keyed format: the question's id, else its header, else
+    /// its text. A header another answer already uses falls back to the
+    /// question text, and whatever still clashes gets " (2)", " (3)"...
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for a in &self.answers {
+            let key = match (&a.id, a.header.is_empty()) {
+                (Some(id), _) => id.clone(),
+                (None, false) if !keys.contains(&a.header) => a.header.clone(),
+                _ => a.question.clone(),
+            };
+            let mut unique = key.clone();
+            for n in 2.. {
+                if !keys.contains(&unique) {
+                    break;
+                }
+                unique = format!("{} ({})", key, n);
+            }
+            keys.push(unique);
+        }
+        keys
+    }
+
+    fn to_json(&self, format: AnswersFormat) -> serde_json::Value {
+        match format {
+            AnswersFormat::List => serde_json::to_value(self).unwrap_or_default(),
+            AnswersFormat::Keyed => {
+                let keys = self.keys();
+                let answers: serde_json::Map<String, serde_json::Value> = self.answers.iter().zip(&keys).map(|(a, key)| {
+                    // "Other" text reads like one more chosen value here
+                    let value = match (a.value.clone().or_else(|| a.selected.clone()), &a.other) {
+                        (Some(serde_json::Value::Array(mut values)), Some(other)) => {
+                            values.push(serde_json::json!(other));
+                            serde_json::Value::Array(values)
+                        }
+                        (Some(value), _) => value,
+                        (None, Some(other)) => serde_json::json!(other),
+                        (None, None) => serde_json::json!(""),
+                    };
+                    (key.clone(), value)
+                }).collect();
+                let notes: serde_json::Map<String, serde_json::Value> = self.answers.iter().zip(&keys)
+                    .filter_map(|(a, key)| Some((key.clone(), serde_json::json!(a.note.as_ref()?))))
+                    .collect();
+                let mut out = serde_json::json!({ "status": self.status, "answers": answers });
+                if !notes.is_empty() {
+                    out["notes"] = serde_json::Value::Object(notes);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// How a dialog ended.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "status", content = "answers", rename_all = "lowercase")]
+enum Outcome {
+    Selected(Vec<AnswerData>),
+    /// The window was closed mid-flow; carries whatever had been answered.
+    Partial(Vec<AnswerData>),
+    Cancelled,
+    /// Answers to a confirmation prompt.
+    Approved,
+    Denied,
+}
+
+/// A user action that changes dialog state. Every change goes through
+/// `App::apply` so sessions can be recorded and replayed exactly.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Event {
+    Toggle { option: usize },
+    /// Matrix questions: toggle `column` in `row`.
+    ToggleCell { row: usize, column: usize },
+    /// Choose (or, in multi-select, toggle) the "Other" answer.
+    ToggleOther,
+    SetCustom { text: String },
+    SetRemember { remember: bool },
+    SetNote { text: String },
+    Next,
+    Back,
+    Submit,
+    Cancel,
+    /// The window was closed without submitting or cancelling.
+    Close,
+    /// Pick up a previous session from its saved draft.
+    Restore { draft: Draft },
+    /// Decline the saved draft and start over.
+    Discard,
+    Approve,
+    Deny,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct AnswerData {
+    #[serde(default)]
+    id: Option<String>,
     question: String,
     header: String,
     selected: Vec<String>,
     indices: Vec<i32>,
+    #[serde(default)]
+    values: Option<Vec<String>>,
+    #[serde(default)]
+    other: Option<String>,
     multi: bool,
+    /// The user ticked "Remember my answer".
+    remember: bool,
+    /// Answered from the store instead of asking.
+    remembered: bool,
+    #[serde(default)]
+    accepted_default: Option<bool>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    relative_path: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    /// Matrix questions: each row's answer.
+    #[serde(default)]
+    rows: Option<Vec<RowAnswer>>,
+    /// Answers a secret question; its text never leaves the process except
+    /// in the printed response.
+    #[serde(default)]
+    secret: bool,
+}
+
+// Every copy of a secret answer is wiped as it goes, wherever it ends up
+impl Drop for AnswerData {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// One row of a matrix question's answer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RowAnswer {
+    /// The row's `value`, or its label when it has none.
+    key: String,
+    selected: Vec<String>,
+    indices: Vec<i32>,
+    #[serde(default)]
+    values: Option<Vec<String>>,
+}
+
+impl AnswerData {
+    fn for_path(q: &Question, picked: &pathpick::Picked) -> Self {
+        let path = picked.absolute.display().to_string();
+        Self {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            values: q.id.is_some().then(|| vec![path.clone()]),
+            selected: vec![path.clone()],
+            indices: Vec::new(),
+            other: None,
+            multi: false,
+            remember: false,
+            remembered: false,
+            accepted_default: None,
+            path: Some(path),
+            relative_path: Some(picked.relative.display().to_string()),
+            note: None,
+            rows: None,
+            secret: false,
+        }
+    }
+
+    fn for_when(q: &Question, value: &when::When) -> Self {
+        let iso = when::iso(q, value);
+        Self {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            values: q.id.is_some().then(|| vec![iso.clone()]),
+            selected: vec![iso],
+            indices: Vec::new(),
+            other: None,
+            multi: false,
+            remember: false,
+            remembered: false,
+            accepted_default: None,
+            path: None,
+            relative_path: None,
+            note: None,
+            rows: None,
+            secret: false,
+        }
+    }
+
+    fn for_secret(q: &Question, text: &str) -> Self {
+        Self {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            values: q.id.is_some().then(|| vec![text.to_string()]),
+            selected: vec![text.to_string()],
+            indices: Vec::new(),
+            other: None,
+            multi: false,
+            remember: false,
+            remembered: false,
+            accepted_default: None,
+            path: None,
+            relative_path: None,
+            note: None,
+            rows: None,
+            secret: true,
+        }
+    }
+
+    /// A secret answer whose text a draft left out, to be asked again.
+    fn is_wiped(&self) -> bool {
+        self.secret && self.selected.is_empty()
+    }
+
+    /// Wipes the text of a secret answer.
+    fn zeroize(&mut self) {
+        if self.secret {
+            self.selected.zeroize();
+            self.values.zeroize();
+        }
+    }
+
+    fn for_matrix(q: &Question, selected: &[bool]) -> Self {
+        let rows: Vec<RowAnswer> = q.rows.iter().enumerate().map(|(r, row)| {
+            let indices: Vec<i32> = (0..q.options.len())
+                .filter(|&c| selected.get(q.cell(r, c)).copied().unwrap_or(false))
+                .map(|c| c as i32)
+                .collect();
+            let labels: Vec<String> = indices.iter().map(|&c| q.options[c as usize].label.clone()).collect();
+            RowAnswer {
+                key: row.key().to_string(),
+                values: q.values_for(&labels, &indices),
+                selected: labels,
+                indices,
+            }
+        }).collect();
+        Self {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            // One "row: choice" line per row, for the review screen
+            selected: q.rows.iter().zip(&rows).map(|(row, a)| format!("{}: {}", row.label, a.selected.join(", "))).collect(),
+            indices: Vec::new(),
+            values: None,
+            other: None,
+            multi: q.multi_select,
+            remember: false,
+            remembered: false,
+            accepted_default: None,
+            path: None,
+            relative_path: None,
+            note: None,
+            rows: Some(rows),
+            secret: false,
+        }
+    }
 }
 
 struct Theme {
+    scheme: ColorScheme,
+
     // Base colors
     bg: egui::Color32,
 
@@ -81,15 +948,35 @@ struct Theme {
     // Status colors
     success: egui::Color32,
     success_muted: egui::Color32,
+    danger: egui::Color32,
+    danger_hover: egui::Color32,
+    danger_muted: egui::Color32,
 
     // Border
     border: egui::Color32,
     border_subtle: egui::Color32,
+
+    // Type scale, in points before the user's zoom
+    size_badge: f32,
+    size_label: f32,
+    size_caption: f32,
+    size_body: f32,
+    size_option: f32,
+    size_title: f32,
 }
 
 impl Theme {
-    fn new() -> Self {
+    fn new(scheme: ColorScheme) -> Self {
+        match scheme {
+            ColorScheme::Dark => Self::dark(),
+            ColorScheme::Light => Self::light(),
+        }
+    }
+
+    fn dark() -> Self {
         Self {
+            scheme: ColorScheme::Dark,
+
             // Deep, rich background
             bg: egui::Color32::from_rgb(8, 8, 12),
 
@@ -113,15 +1000,71 @@ impl Theme {
             success: egui::Color32::from_rgb(74, 222, 128),
             success_muted: egui::Color32::from_rgb(30, 70, 50),
 
+            // Danger - muted red for destructive actions
+            danger: egui::Color32::from_rgb(239, 68, 68),
+            danger_hover: egui::Color32::from_rgb(248, 113, 113),
+            danger_muted: egui::Color32::from_rgb(80, 28, 32),
+
             // Borders
             border: egui::Color32::from_rgb(45, 45, 60),
             border_subtle: egui::Color32::from_rgb(30, 30, 42),
+
+            // Badges and small caps headers up to question titles
+            size_badge: 9.0,
+            size_label: 10.0,
+            size_caption: 11.0,
+            size_body: 12.0,
+            size_option: 13.0,
+            size_title: 17.0,
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            scheme: ColorScheme::Light,
+
+            // Soft paper background with white cards
+            bg: egui::Color32::from_rgb(244, 244, 248),
+
+            surface: egui::Color32::from_rgb(255, 255, 255),
+            surface_hover: egui::Color32::from_rgb(236, 236, 244),
+            surface_active: egui::Color32::from_rgb(224, 224, 238),
+
+            text_primary: egui::Color32::from_rgb(17, 17, 24),
+            text_secondary: egui::Color32::from_rgb(68, 68, 84),
+            text_muted: egui::Color32::from_rgb(120, 120, 138),
+            text_inverse: egui::Color32::from_rgb(255, 255, 255),
+
+            // Same blue-violet, a shade deeper for contrast on white
+            accent: egui::Color32::from_rgb(79, 70, 229),
+            accent_hover: egui::Color32::from_rgb(99, 102, 241),
+            accent_muted: egui::Color32::from_rgb(224, 224, 252),
+
+            success: egui::Color32::from_rgb(22, 146, 74),
+            success_muted: egui::Color32::from_rgb(218, 246, 228),
+
+            danger: egui::Color32::from_rgb(220, 38, 38),
+            danger_hover: egui::Color32::from_rgb(239, 68, 68),
+            danger_muted: egui::Color32::from_rgb(254, 226, 226),
+
+            border: egui::Color32::from_rgb(212, 212, 224),
+            border_subtle: egui::Color32::from_rgb(228, 228, 236),
+
+            ..Self::dark()
         }
     }
 }
 
+/// A line in the compact option list.
+enum ListRow {
+    Heading(String),
+    Option(usize),
+}
+
 #[derive(Clone)]
 enum Screen {
+    Restore,
+    Confirm,
     Question(usize),
     Review,
 }
@@ -140,6 +1083,13 @@ impl HoverState {
         self.target = if hovered { 1.0 } else { 0.0 };
         let speed = 12.0;
         self.current += (self.target - self.current) * (speed * dt).min(1.0);
+        if self.settled() {
+            self.current = self.target;
+        }
+    }
+
+    fn settled(&self) -> bool {
+        (self.target - self.current).abs() < 0.01
     }
 
     fn value(&self) -> f32 {
@@ -155,150 +1105,708 @@ struct App {
     hover_states: Vec<HoverState>,
     custom: String,
     custom_focused: bool,
-    tx: mpsc::Sender<Option<Vec<QuestionAnswer>>>,
+    /// "Other" is chosen; `custom` holds its text.
+    other: bool,
+    remember: bool,
+    note: String,
+    note_open: bool,
+    note_focused: bool,
+    filter: String,
+    filter_focused: bool,
+    /// Highlighted entry among the filtered options.
+    list_cursor: usize,
+    /// Scroll offset to apply to the option list on the next frame.
+    list_scroll_to: Option<f32>,
+    list_scroll: f32,
+    list_view_height: f32,
+    /// Folder shown in the path browser, and its cached listing.
+    browse_dir: PathBuf,
+    browse_entries: Vec<pathpick::Entry>,
+    browse_loaded: Option<PathBuf>,
+    path_check: pathpick::Checked,
+    /// First day of the month the date picker shows.
+    calendar: jiff::civil::Date,
+    /// Show a secret question's text instead of dots.
+    reveal: bool,
+    tx: mpsc::Sender<Outcome>,
     theme: Theme,
+    /// Zoom factor Ctrl/Cmd+0 returns to.
+    scale: f32,
+    /// Lay out right to left.
+    rtl: bool,
+    reduced_motion: bool,
+    fonts: Option<Fonts>,
     transition_progress: f32,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    drafts: Option<DraftStore>,
+    confirm: Option<Confirm>,
+    review: ReviewMode,
+    /// Set for real windows; drives content sizing and remembered geometry.
+    window: Option<Placement>,
+    window_size: Option<egui::Vec2>,
+    size_requested: Option<egui::Vec2>,
+    user_sized: bool,
+    offered_draft: Option<Draft>,
+    /// Where a restored draft left off, returned to once the secrets it
+    /// couldn't keep are asked again.
+    resume: Option<Draft>,
+    draft_dirty: bool,
+    draft_saved: Instant,
+    closing: bool,
+    /// One of several dialogs sharing the window (see `broker`).
+    queued: bool,
+    requester: Requester,
+    /// The window title has been set to this dialog's.
+    titled: bool,
+    images: images::Images,
+    /// Image shown full size over the dialog.
+    zoomed: Option<String>,
+}
+
+/// Events from a trace waiting to be fed back into the dialog, keyed by
+/// their offset from the start of the session.
+struct Replay {
+    events: VecDeque<(u64, Event)>,
+    start: Instant,
 }
 
 impl App {
-    fn new(questions: Vec<Question>, tx: mpsc::Sender<Option<Vec<QuestionAnswer>>>) -> Self {
-        let n = questions.first().map(|q| q.options.len()).unwrap_or(0);
-        Self {
+    fn new(questions: Vec<Question>, tx: mpsc::Sender<Outcome>) -> Self {
+        let mut app = Self {
             questions,
             screen: Screen::Question(0),
             answers: Vec::new(),
-            selected: vec![false; n],
-            hover_states: (0..n + 1).map(|_| HoverState::new()).collect(),
+            selected: Vec::new(),
+            hover_states: Vec::new(),
             custom: String::new(),
             custom_focused: false,
+            other: false,
+            remember: false,
+            note: String::new(),
+            note_open: false,
+            note_focused: false,
+            filter: String::new(),
+            filter_focused: false,
+            list_cursor: 0,
+            list_scroll_to: None,
+            list_scroll: 0.0,
+            list_view_height: 0.0,
+            browse_dir: PathBuf::new(),
+            browse_entries: Vec::new(),
+            browse_loaded: None,
+            path_check: pathpick::Checked::default(),
+            calendar: jiff::civil::Date::default(),
+            reveal: false,
             tx,
-            theme: Theme::new(),
+            theme: Theme::dark(),
+            scale: 1.0,
+            rtl: false,
+            reduced_motion: false,
+            fonts: None,
             transition_progress: 0.0,
+            recorder: None,
+            replay: None,
+            drafts: None,
+            confirm: None,
+            review: ReviewMode::Always,
+            window: None,
+            window_size: None,
+            size_requested: None,
+            user_sized: false,
+            offered_draft: None,
+            resume: None,
+            draft_dirty: false,
+            draft_saved: Instant::now(),
+            closing: false,
+            queued: false,
+            requester: Requester::default(),
+            titled: false,
+            images: images::Images::default(),
+            zoomed: None,
+        };
+        if !app.questions.is_empty() {
+            app.show_question(0);
         }
+        app
     }
 
-    fn current_q(&self) -> Option<&Question> {
-        if let Screen::Question(idx) = self.screen {
-            self.questions.get(idx)
-        } else {
-            None
-        }
+    /// A dialog asking to approve `confirm` rather than answer questions.
+    fn confirm(confirm: Confirm, tx: mpsc::Sender<Outcome>) -> Self {
+        let mut app = Self::new(Vec::new(), tx);
+        app.confirm = Some(confirm);
+        app.screen = Screen::Confirm;
+        app
     }
 
-    fn idx(&self) -> usize {
-        if let Screen::Question(idx) = self.screen { idx } else { self.questions.len() }
+    fn with_review(mut self, review: ReviewMode) -> Self {
+        self.review = review;
+        self
     }
 
-    fn get_selection(&self) -> (Vec<String>, Vec<i32>) {
-        let q = match self.current_q() {
-            Some(q) => q,
-            None => return (vec![], vec![]),
-        };
+    fn with_placement(mut self, placement: Placement) -> Self {
+        self.user_sized = placement.user_size;
+        self.window = Some(placement);
+        self
+    }
 
-        let mut labels = Vec::new();
-        let mut indices = Vec::new();
+    fn with_appearance(mut self, ctx: &egui::Context, appearance: &Appearance) -> Self {
+        let fonts = Fonts::new(&appearance.fonts, &appearance.fallback_fonts, appearance.system_fonts);
+        fonts.install(ctx);
+        self.fonts = Some(fonts);
+        self.scale = appearance.scale;
+        self.rtl = appearance.direction.is_rtl(&self.shown_text());
+        self.reduced_motion = appearance.reduced_motion;
+        self.theme = Theme::new(appearance.theme);
+        // Zoom is handled in `update` so Ctrl/Cmd+0 returns to `scale`
+        ctx.options_mut(|o| o.zoom_with_keyboard = false);
+        ctx.set_zoom_factor(appearance.scale);
+        self
+    }
 
-        for (i, opt) in q.options.iter().enumerate() {
-            if self.selected.get(i).copied().unwrap_or(false) {
-                labels.push(opt.label.clone());
-                indices.push(i as i32);
-            }
+    /// Resolves relative paths and images against `cwd` rather than this
+    /// process's working directory.
+    fn with_cwd(mut self, cwd: Option<PathBuf>) -> Self {
+        for q in &mut self.questions {
+            q.cwd = cwd.clone();
         }
-
-        if !self.custom.trim().is_empty() {
-            labels.push(self.custom.trim().to_string());
-            indices.push(-1);
+        self.images.dir = cwd;
+        if let Screen::Question(idx) = self.screen {
+            self.show_question(idx);
         }
+        self
+    }
 
-        (labels, indices)
+    fn with_requester(mut self, requester: Requester) -> Self {
+        self.requester = requester;
+        self
     }
 
-    fn has_selection(&self) -> bool {
-        self.selected.iter().any(|&s| s) || !self.custom.trim().is_empty()
+    fn with_recorder(mut self, recorder: Option<Recorder>) -> Self {
+        self.recorder = recorder;
+        self
     }
 
-    fn save_answer(&mut self) {
-        if let Some(q) = self.current_q().cloned() {
-            let (labels, indices) = self.get_selection();
-            if !labels.is_empty() {
-                self.answers.push(AnswerData {
-                    question: q.question,
-                    header: q.header,
-                    selected: labels,
-                    indices,
-                    multi: q.multi_select,
-                });
-            }
-        }
+    fn with_replay(mut self, events: Vec<(u64, Event)>) -> Self {
+        self.replay = Some(Replay { events: events.into(), start: Instant::now() });
+        self
     }
 
-    fn go_next(&mut self) {
-        self.save_answer();
-        let next_idx = self.idx() + 1;
-        if next_idx < self.questions.len() {
-            self.screen = Screen::Question(next_idx);
-            let n = self.questions[next_idx].options.len();
-            self.selected = vec![false; n];
-            self.hover_states = (0..n + 1).map(|_| HoverState::new()).collect();
-            self.custom.clear();
-            self.transition_progress = 0.0;
-        } else {
-            self.screen = Screen::Review;
-            self.transition_progress = 0.0;
+    /// Persist progress to `store`, first offering to restore whatever an
+    /// earlier session left there.
+    fn with_drafts(mut self, store: Option<DraftStore>) -> Self {
+        if let Some(draft) = store.as_ref().and_then(|s| s.load()) {
+            self.offered_draft = Some(draft);
+            self.screen = Screen::Restore;
         }
+        self.drafts = store;
+        self
     }
 
-    fn go_back(&mut self) {
-        match self.screen {
-            Screen::Question(idx) if idx > 0 => {
-                self.answers.pop();
-                self.screen = Screen::Question(idx - 1);
-                let n = self.questions[idx - 1].options.len();
-                self.selected = vec![false; n];
-                self.hover_states = (0..n + 1).map(|_| HoverState::new()).collect();
-                self.custom.clear();
-                self.transition_progress = 0.0;
+    fn apply(&mut self, event: Event) {
+        if self.closing {
+            return;
+        }
+        let secret = self.current_q().is_some_and(|q| q.kind == QuestionKind::Secret);
+        if let Some(rec) = &self.recorder {
+            // A placeholder keeps replays answering the question
+            match &event {
+                Event::SetCustom { .. } if secret => rec.event(&Event::SetCustom { text: SECRET_PLACEHOLDER.into() }),
+                event => rec.event(event),
             }
-            Screen::Review => {
-                self.answers.pop();
-                let last = self.questions.len() - 1;
-                self.screen = Screen::Question(last);
-                let n = self.questions[last].options.len();
-                self.selected = vec![false; n];
-                self.hover_states = (0..n + 1).map(|_| HoverState::new()).collect();
-                self.custom.clear();
-                self.transition_progress = 0.0;
+        }
+        match event {
+            Event::Toggle { option } => self.toggle(option),
+            Event::ToggleCell { row, column } => self.toggle_cell(row, column),
+            Event::ToggleOther => self.toggle_other(),
+            Event::SetCustom { text } => self.set_custom(text),
+            Event::SetRemember { remember } => self.remember = remember,
+            Event::SetNote { text } => self.note = text,
+            Event::Next => self.go_next(),
+            Event::Back => self.go_back(),
+            Event::Submit => self.submit(),
+            Event::Cancel => self.cancel(),
+            Event::Close => self.close(),
+            Event::Restore { draft } => self.restore(draft),
+            Event::Discard => {
+                if let Some(store) = &self.drafts { store.discard(); }
+                self.show_question(0);
+            }
+            Event::Approve => {
+                if self.confirm.as_ref().is_some_and(|c| c.unlocked(&self.custom)) {
+                    let _ = self.tx.send(Outcome::Approved);
+                    self.closing = true;
+                }
+            }
+            Event::Deny => {
+                let _ = self.tx.send(Outcome::Denied);
+                self.closing = true;
             }
-            _ => {}
+        }
+        if !self.closing {
+            self.draft_dirty = true;
         }
     }
 
-    fn submit(&mut self, ctx: &egui::Context) {
-        let answers: Vec<QuestionAnswer> = self.answers.iter().map(|a| {
-            if a.multi {
-                QuestionAnswer {
-                    question: a.question.clone(),
-                    header: a.header.clone(),
-                    selected: Some(serde_json::json!(a.selected)),
-                    selected_index: Some(serde_json::json!(a.indices)),
-                }
-            } else {
-                QuestionAnswer {
-                    question: a.question.clone(),
-                    header: a.header.clone(),
-                    selected: a.selected.first().map(|s| serde_json::json!(s)),
-                    selected_index: a.indices.first().map(|i| serde_json::json!(i)),
-                }
-            }
+    fn snapshot(&self) -> Draft {
+        // Secret answers keep their slot but not their text
+        let answers = self.answers.iter().map(|a| {
+            let mut a = a.clone();
+            a.zeroize();
+            a
         }).collect();
+        if let Some(resume) = &self.resume {
+            return Draft { answers, ..resume.clone() };
+        }
+        let secret = self.current_q().is_some_and(|q| q.kind == QuestionKind::Secret);
+        Draft {
+            answers,
+            screen: self.idx(),
+            selected: self.selected.clone(),
+            custom: if secret { String::new() } else { self.custom.clone() },
+            other: self.other,
+            remember: self.remember,
+            note: self.note.clone(),
+        }
+    }
+
+    fn save_draft(&mut self) {
+        self.draft_dirty = false;
+        self.draft_saved = Instant::now();
+        let Some(store) = &self.drafts else { return };
+        if matches!(self.screen, Screen::Restore) {
+            return;
+        }
+        if self.answers.is_empty() && !self.has_selection() {
+            store.discard();
+        } else {
+            store.save(&self.snapshot());
+        }
+    }
+
+    fn restore(&mut self, mut draft: Draft) {
+        self.offered_draft = None;
+        let total = self.questions.len();
+        if draft.screen > total || draft.answers.len() != draft.screen {
+            self.show_question(0);
+            return;
+        }
+        self.answers = std::mem::take(&mut draft.answers);
+        if let Some(wiped) = self.answers.iter().position(AnswerData::is_wiped) {
+            self.resume = Some(draft);
+            self.show_question(wiped);
+            return;
+        }
+        self.resume_at(draft);
+    }
+
+    /// Puts back the screen a draft was saved on.
+    fn resume_at(&mut self, draft: Draft) {
+        if draft.screen < self.questions.len() {
+            self.show_question(draft.screen);
+            if draft.selected.len() == self.selected.len() {
+                self.selected = draft.selected;
+            }
+            self.custom = draft.custom;
+            self.other = draft.other;
+            self.remember = draft.remember;
+            self.note_open = !draft.note.is_empty();
+            self.note = draft.note;
+        } else {
+            self.screen = Screen::Review;
+            self.transition_progress = 0.0;
+        }
+    }
+
+    fn toggle(&mut self, option: usize) {
+        let multi = self.current_q().map(|q| q.multi_select).unwrap_or(false);
+        if multi {
+            if let Some(s) = self.selected.get_mut(option) { *s = !*s; }
+        } else {
+            // Other's text is kept in case the user switches back to it
+            self.selected.iter_mut().enumerate().for_each(|(j, s)| *s = j == option);
+            self.other = false;
+        }
+    }
+
+    fn toggle_cell(&mut self, row: usize, column: usize) {
+        let Some(q) = self.current_q().filter(|q| q.kind == QuestionKind::Matrix && column < q.options.len()) else { return };
+        let (cell, start, multi) = (q.cell(row, column), q.cell(row, 0), q.multi_select);
+        let end = start + q.options.len();
+        if multi {
+            if let Some(s) = self.selected.get_mut(cell) { *s = !*s; }
+        } else if let Some(cells) = self.selected.get_mut(start..end) {
+            cells.iter_mut().enumerate().for_each(|(c, s)| *s = c == column);
+        }
+    }
+
+    /// Matrix questions: whether `row` has something chosen.
+    fn row_answered(&self, row: usize) -> bool {
+        let Some(q) = self.current_q() else { return false };
+        (0..q.options.len()).any(|c| self.selected.get(q.cell(row, c)).copied().unwrap_or(false))
+    }
+
+    fn toggle_other(&mut self) {
+        let multi = self.current_q().map(|q| q.multi_select).unwrap_or(false);
+        if multi {
+            self.other = !self.other;
+        } else {
+            self.selected.iter_mut().for_each(|s| *s = false);
+            self.other = true;
+        }
+    }
+
+    fn set_custom(&mut self, text: String) {
+        // Typing into Other chooses it
+        if !text.is_empty() && !self.other && self.current_q().is_some_and(|q| q.kind.is_choice()) {
+            self.toggle_other();
+        }
+        // No stale copies of a secret are left behind
+        std::mem::replace(&mut self.custom, text).zeroize();
+    }
+
+    /// The "Other" text, if that is part of the answer.
+    fn other_text(&self) -> Option<String> {
+        let q = self.current_q()?;
+        let text = self.custom.trim();
+        (q.kind.is_choice() && q.allow_other && self.other && !text.is_empty()).then(|| text.to_string())
+    }
+
+    fn current_q(&self) -> Option<&Question> {
+        if let Screen::Question(idx) = self.screen {
+            self.questions.get(idx)
+        } else {
+            None
+        }
+    }
+
+    fn idx(&self) -> usize {
+        if let Screen::Question(idx) = self.screen { idx } else { self.questions.len() }
+    }
+
+    fn get_selection(&self) -> (Vec<String>, Vec<i32>) {
+        let q = match self.current_q() {
+            Some(q) => q,
+            None => return (vec![], vec![]),
+        };
+
+        if q.kind == QuestionKind::Path {
+            return match self.path_check.check(q, &self.custom) {
+                Ok(picked) => (vec![picked.absolute.display().to_string()], vec![]),
+                Err(_) => (vec![], vec![]),
+            };
+        }
+
+        let mut labels = Vec::new();
+        let mut indices = Vec::new();
+
+        for (i, opt) in q.options.iter().enumerate() {
+            if self.selected.get(i).copied().unwrap_or(false) {
+                labels.push(opt.label.clone());
+                indices.push(i as i32);
+            }
+        }
+
+        (labels, indices)
+    }
+
+    fn has_selection(&self) -> bool {
+        match self.current_q() {
+            Some(q) if q.kind == QuestionKind::Path => self.path_check.check(q, &self.custom).is_ok(),
+            Some(q) if q.kind == QuestionKind::Matrix => (0..q.rows.len()).all(|r| self.row_answered(r)),
+            Some(q) if q.kind.is_when() => when::check(q, &self.custom).is_ok(),
+            Some(q) if q.kind == QuestionKind::Secret => !self.custom.is_empty(),
+            _ => self.selected.iter().any(|&s| s) || self.other_text().is_some(),
+        }
+    }
+
+    fn current_answer(&self) -> Option<AnswerData> {
+        let q = self.current_q()?;
+        if q.kind == QuestionKind::Path {
+            let picked = self.path_check.check(q, &self.custom).ok()?;
+            let mut answer = AnswerData::for_path(q, &picked);
+            answer.remember = self.remember && q.remember_key.is_some();
+            answer.note = self.current_note();
+            return Some(answer);
+        }
+        if q.kind.is_when() {
+            let value = when::check(q, &self.custom).ok()?;
+            let mut answer = AnswerData::for_when(q, &value);
+            answer.remember = self.remember && q.remember_key.is_some();
+            answer.note = self.current_note();
+            return Some(answer);
+        }
+        if q.kind == QuestionKind::Secret {
+            return self.has_selection().then(|| {
+                let mut answer = AnswerData::for_secret(q, &self.custom);
+                answer.note = self.current_note();
+                answer
+            });
+        }
+        if q.kind == QuestionKind::Matrix {
+            return self.has_selection().then(|| {
+                let mut answer = AnswerData::for_matrix(q, &self.selected);
+                answer.note = self.current_note();
+                answer
+            });
+        }
+        let (labels, indices) = self.get_selection();
+        let other = self.other_text();
+        if labels.is_empty() && other.is_none() {
+            return None;
+        }
+        let has_default = q.options.iter().any(|o| o.default);
+        let accepted_default = has_default.then(|| {
+            other.is_none()
+                && q.options.iter().zip(&self.selected).all(|(o, &s)| o.default == s)
+        });
+        Some(AnswerData {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            values: q.values_for(&labels, &indices),
+            selected: labels,
+            indices,
+            other,
+            multi: q.multi_select,
+            remember: self.remember && q.remember_key.is_some(),
+            remembered: false,
+            accepted_default,
+            path: None,
+            relative_path: None,
+            note: self.current_note(),
+            rows: None,
+            secret: false,
+        })
+    }
+
+    fn current_note(&self) -> Option<String> {
+        let note = self.note.trim();
+        (!note.is_empty()).then(|| note.to_string())
+    }
+
+    fn save_answer(&mut self) {
+        let Some(answer) = self.current_answer() else { return };
+        let idx = self.idx();
+        match self.answers.get_mut(idx) {
+            // A secret asked again after restoring a draft
+            Some(slot) => *slot = answer,
+            None => self.answers.push(answer),
+        }
+    }
+
+    fn show_question(&mut self, idx: usize) {
+        self.screen = Screen::Question(idx);
+        let q = &self.questions[idx];
+        let n = q.options.len();
+        let mut defaults: Vec<bool> = q.options.iter().map(|o| o.default).collect();
+        if !q.multi_select {
+            // A radio group can only start with one default
+            let first = defaults.iter().position(|&d| d);
+            defaults.iter_mut().enumerate().for_each(|(i, s)| *s = Some(i) == first);
+        }
+        // Each matrix row starts from the column defaults
+        self.selected = if q.kind == QuestionKind::Matrix { defaults.repeat(q.rows.len()) } else { defaults };
+        self.hover_states = (0..n + 1).map(|_| HoverState::new()).collect();
+        self.custom.zeroize();
+        self.reveal = false;
+        self.other = false;
+        self.remember = false;
+        self.note.clear();
+        self.note_open = false;
+        self.filter.clear();
+        self.list_cursor = 0;
+        self.list_scroll_to = Some(0.0);
+        if q.kind == QuestionKind::Path {
+            self.browse_dir = pathpick::base_dir(q);
+            // Another question may list the same folder differently
+            self.browse_loaded = None;
+            self.path_check.clear();
+        }
+        if q.kind.is_when() {
+            self.custom = q.default.clone().unwrap_or_default();
+            if let Some(day) = when::day(&when::start(q)) {
+                self.calendar = day.first_of_month();
+            }
+        }
+        self.transition_progress = 0.0;
+    }
+
+    fn go_next(&mut self) {
+        // The path may have come or gone since it was last looked at
+        self.path_check.clear();
+        // As in the dialog, there's no moving on without an answer; replayed
+        // events must not leave later answers out of line
+        if !self.has_selection() {
+            return;
+        }
+        self.save_answer();
+        // Past the answers a restored draft kept, to its next secret
+        let next_idx = (self.idx() + 1..).find(|&i| self.answers.get(i).is_none_or(AnswerData::is_wiped)).unwrap_or_default();
+        if next_idx == self.answers.len() {
+            if let Some(resume) = self.resume.take() {
+                self.resume_at(resume);
+                return;
+            }
+        }
+        if next_idx < self.questions.len() {
+            self.show_question(next_idx);
+        } else if self.skips_review() {
+            self.submit();
+        } else {
+            self.screen = Screen::Review;
+            self.transition_progress = 0.0;
+        }
+    }
+
+    fn skips_review(&self) -> bool {
+        match self.review {
+            ReviewMode::Always => false,
+            ReviewMode::Never => true,
+            ReviewMode::Auto => self.questions.len() == 1,
+        }
+    }
+
+    /// Toggles an option from a click or key press, moving straight on for
+    /// submit-on-select questions.
+    fn pick(&mut self, option: usize) {
+        self.apply(Event::Toggle { option });
+        let fast = self.current_q().is_some_and(|q| q.submit_on_select && !q.multi_select);
+        if fast && self.has_selection() {
+            self.apply(Event::Next);
+        }
+    }
 
-        let _ = self.tx.send(Some(answers));
-        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    fn go_back(&mut self) {
+        match self.screen {
+            Screen::Question(idx) if idx > 0 => {
+                self.answers.truncate(idx - 1);
+                self.resume = None;
+                self.show_question(idx - 1);
+            }
+            Screen::Review => {
+                self.answers.truncate(self.questions.len() - 1);
+                self.show_question(self.questions.len() - 1);
+            }
+            _ => {}
+        }
+    }
+
+    fn submit(&mut self) {
+        if let Some(store) = &self.drafts { store.discard(); }
+        let _ = self.tx.send(Outcome::Selected(self.answers.clone()));
+        self.closing = true;
+        self.wipe_secrets();
     }
 
-    fn cancel(&self, ctx: &egui::Context) {
-        let _ = self.tx.send(None);
-        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    fn cancel(&mut self) {
+        if let Some(store) = &self.drafts { store.discard(); }
+        let _ = self.tx.send(Outcome::Cancelled);
+        self.closing = true;
+        self.wipe_secrets();
+    }
+
+    /// Keep the draft for next time and report whatever was answered,
+    /// including the selection on the current screen.
+    fn close(&mut self) {
+        if self.confirm.is_some() {
+            let _ = self.tx.send(Outcome::Denied);
+            self.closing = true;
+            return;
+        }
+        self.save_draft();
+        let mut answers = self.answers.clone();
+        if let Some(answer) = self.current_answer() {
+            match answers.get_mut(self.idx()) {
+                Some(slot) => *slot = answer,
+                None => answers.push(answer),
+            }
+        }
+        // A secret still to be asked again ends the answers, so later
+        // ones keep their place
+        if let Some(wiped) = answers.iter().position(AnswerData::is_wiped) {
+            answers.truncate(wiped);
+        }
+        let outcome = if answers.is_empty() { Outcome::Cancelled } else { Outcome::Partial(answers) };
+        let _ = self.tx.send(outcome);
+        self.closing = true;
+        self.wipe_secrets();
+    }
+
+    /// Once the answers are handed over, the dialog keeps no secrets.
+    fn wipe_secrets(&mut self) {
+        self.custom.zeroize();
+        self.answers.iter_mut().for_each(AnswerData::zeroize);
+    }
+
+    /// Fits the window height to `content_height` until the user resizes
+    /// it themselves.
+    fn fit_window(&mut self, ctx: &egui::Context, content_height: f32) {
+        let Some(window) = &self.window else { return };
+        let Some(inner) = ctx.input(|i| i.viewport().inner_rect) else { return };
+        // Sizes are compared in physical pixels so zooming isn't taken for
+        // the user resizing
+        let ppp = ctx.pixels_per_point();
+        let size = inner.size() * ppp;
+        // A size change we didn't ask for is the user resizing
+        let changed = self.window_size.is_some_and(|prev| (prev - size).length() > 1.0);
+        if changed && self.size_requested.is_none_or(|r| (r - size).length() > 1.0) {
+            self.user_sized = true;
+        }
+        self.window_size = Some(size);
+        if self.user_sized {
+            return;
+        }
+
+        // Keep the width constant in points so zooming scales the window
+        let max_size = window.max_size / ctx.zoom_factor();
+        let target = egui::vec2(window.size.x, content_height + 48.0)
+            .clamp(window::MIN_SIZE, max_size.max(window::MIN_SIZE));
+        let target_px = target * ppp;
+        if (target_px - size).length() > 1.0 && self.size_requested.is_none_or(|r| (r - target_px).length() > 1.0) {
+            self.size_requested = Some(target_px);
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(target));
+        }
+    }
+
+    /// Saves the window's position if the user moved it, and its size if
+    /// they resized it.
+    fn remember_geometry(&mut self, ctx: &egui::Context) {
+        let Some(window) = self.window.take() else { return };
+        let outer = ctx.input(|i| i.viewport().outer_rect);
+        let zoom = ctx.zoom_factor();
+        let moved = outer.map(|r| (r.min.to_vec2() * zoom).to_pos2()).filter(|p| window.position.is_none_or(|start| (*p - start).length() > 1.0));
+        // Stored unzoomed, in the display's points
+        let native_ppp = ctx.input(|i| i.viewport().native_pixels_per_point).unwrap_or(1.0);
+        window.remember(moved, self.window_size.filter(|_| self.user_sized).map(|s| s / native_ppp));
+    }
+
+    /// Seconds to advance animations by this frame; with reduced motion,
+    /// enough to finish any of them at once.
+    fn frame_dt(&self, ctx: &egui::Context) -> f32 {
+        if self.reduced_motion { 1.0 } else { ctx.input(|i| i.stable_dt) }
+    }
+
+    /// Whether a transition or hover fade is still in motion.
+    fn animating(&self) -> bool {
+        self.transition_progress < 1.0 || self.hover_states.iter().any(|h| !h.settled())
+    }
+
+    /// Everything the dialog can show, to check the fonts cover it.
+    fn shown_text(&self) -> String {
+        let mut text = String::new();
+        for q in &self.questions {
+            text.extend([q.question.as_str(), &q.header]);
+            for o in q.options.iter().chain(&q.rows) {
+                text.extend([o.label.as_str(), &o.description, o.group.as_deref().unwrap_or_default()]);
+            }
+        }
+        if let Some(c) = &self.confirm {
+            text.extend([c.title.as_str(), &c.body, c.command.as_deref().unwrap_or_default(), c.diff.as_deref().unwrap_or_default()]);
+        }
+        text.extend([self.custom.as_str(), &self.note, &self.filter]);
+        text.push_str(&self.requester.text());
+        text
     }
 
     fn lerp_color(a: egui::Color32, b: egui::Color32, t: f32) -> egui::Color32 {
@@ -311,14 +1819,69 @@ impl App {
         )
     }
 
+    /// Who is asking and why, above the question.
+    fn render_banner(&self, ui: &mut egui::Ui) {
+        let r = &self.requester;
+        if r.is_empty() {
+            return;
+        }
+        egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+            .corner_radius(8)
+            .inner_margin(egui::Margin::symmetric(12, 10))
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.horizontal_top(|ui| {
+                    if let Some(icon) = &r.icon {
+                        egui::Frame::new()
+                            .fill(self.theme.accent_muted)
+                            .corner_radius(6)
+                            .inner_margin(egui::Margin::symmetric(6, 4))
+                            .show(ui, |ui| {
+                                ui.label(egui::RichText::new(icon).color(self.theme.accent_hover).size(self.theme.size_body));
+                            });
+                        ui.add_space(4.0);
+                    }
+                    column(ui, |ui| {
+                        ui.spacing_mut().item_spacing.y = 2.0;
+                        if let Some(title) = &r.title {
+                            bidi::label(ui, egui::RichText::new(title)
+                                .color(self.theme.text_primary)
+                                .size(self.theme.size_body)
+                                .strong());
+                        }
+                        if let Some(line) = r.source.line() {
+                            bidi::label(ui, egui::RichText::new(line)
+                                .color(self.theme.text_muted)
+                                .size(self.theme.size_caption));
+                        }
+                    });
+                });
+                if let Some(context) = &r.context {
+                    ui.add_space(8.0);
+                    markdown::show(ui, context, &markdown::Style {
+                        text: self.theme.text_secondary,
+                        strong: self.theme.text_primary,
+                        link: self.theme.accent_hover,
+                        code_bg: self.theme.bg,
+                        size: self.theme.size_caption + 1.0,
+                    });
+                }
+            });
+        ui.add_space(20.0);
+    }
+
     fn render_question(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, q: Question, idx: usize) {
         let total = self.questions.len();
-        let dt = ctx.input(|i| i.stable_dt);
+        let dt = self.frame_dt(ctx);
 
         // Update transition
         self.transition_progress = (self.transition_progress + dt * 4.0).min(1.0);
         let fade = ease_out_cubic(self.transition_progress);
 
+        self.render_banner(ui);
+
         // Top bar with header and step indicator
         ui.horizontal(|ui| {
             if !q.header.is_empty() {
@@ -327,16 +1890,16 @@ impl App {
                     .corner_radius(4)
                     .inner_margin(egui::Margin::symmetric(8, 3))
                     .show(ui, |ui| {
-                        ui.label(egui::RichText::new(&q.header)
+                        bidi::label(ui, egui::RichText::new(&q.header)
                             .color(self.theme.accent_hover)
-                            .size(10.0)
+                            .size(self.theme.size_label)
                             .strong());
                     });
             }
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.with_layout(trailing(ui), |ui| {
                 ui.label(egui::RichText::new(format!("{} of {}", idx + 1, total))
                     .color(self.theme.text_muted)
-                    .size(11.0));
+                    .size(self.theme.size_caption));
             });
         });
 
@@ -368,8 +1931,10 @@ impl App {
 
                 // Fill
                 if progress > 0.0 {
+                    // Fills from the reading start of the segment
+                    let start = if self.rtl { rect.right_top() - egui::vec2(segment_w * progress, 0.0) } else { rect.min };
                     let fill_rect = egui::Rect::from_min_size(
-                        rect.min,
+                        start,
                         egui::vec2(segment_w * progress, 3.0)
                     );
                     ui.painter().rect_filled(fill_rect, 2.0, color);
@@ -383,25 +1948,610 @@ impl App {
 
         // Question text with fade animation
         let alpha = (fade * 255.0) as u8;
-        ui.label(egui::RichText::new(&q.question)
+        bidi::label(ui, egui::RichText::new(&q.question)
             .color(egui::Color32::from_rgba_unmultiplied(
                 self.theme.text_primary.r(),
                 self.theme.text_primary.g(),
                 self.theme.text_primary.b(),
                 alpha
             ))
-            .size(17.0));
+            .size(self.theme.size_title));
 
-        if q.multi_select {
+        if q.multi_select && q.kind != QuestionKind::Path {
             ui.add_space(6.0);
             ui.label(egui::RichText::new("Select all that apply")
                 .color(self.theme.text_muted)
-                .size(11.0)
+                .size(self.theme.size_caption)
                 .italics());
         }
 
+        if let Some(source) = &q.image {
+            ui.add_space(12.0);
+            let rect = self.draw_image(ui, source, egui::vec2(ui.available_width(), 160.0));
+            self.zoom_on_click(ui, rect, source);
+        }
+
         ui.add_space(18.0);
 
+        match q.kind {
+            QuestionKind::Choice => {
+                if q.is_long_list() {
+                    self.render_option_list(ui, &q);
+                } else {
+                    self.render_option_cards(ui, &q, dt);
+                }
+                if q.allow_other {
+                    ui.add_space(10.0);
+                    self.render_custom_input(ui, &q, dt);
+                }
+            }
+            QuestionKind::Path => self.render_path_picker(ui, &q),
+            QuestionKind::Matrix => self.render_matrix(ui, &q),
+            QuestionKind::Date | QuestionKind::Time | QuestionKind::DateTime | QuestionKind::Duration => self.render_when(ui, &q),
+            QuestionKind::Secret => self.render_secret(ui),
+        }
+
+        ui.add_space(10.0);
+        self.render_note(ui);
+
+        if q.remember_key.is_some() && !matches!(q.kind, QuestionKind::Matrix | QuestionKind::Secret) {
+            ui.add_space(12.0);
+            let row = ui.horizontal(|ui| {
+                self.draw_indicator(ui, self.remember, true, 0.0);
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Remember my answer")
+                    .color(self.theme.text_secondary)
+                    .size(self.theme.size_body));
+            });
+            let click = ui.interact(row.response.rect, ui.id().with("remember"), egui::Sense::click());
+            if click.clicked() {
+                self.apply(Event::SetRemember { remember: !self.remember });
+            }
+        }
+
+        ui.add_space(20.0);
+
+        // Footer buttons
+        ui.horizontal(|ui| {
+            // Back/Cancel button
+            let back_text = if idx > 0 { "Back" } else { "Cancel" };
+            let back_resp = ui.add(
+                egui::Button::new(egui::RichText::new(back_text).color(self.theme.text_muted).size(self.theme.size_body))
+                    .fill(egui::Color32::TRANSPARENT)
+                    .stroke(egui::Stroke::NONE)
+                    .min_size(egui::vec2(70.0, 38.0))
+            );
+            if back_resp.clicked() {
+                self.apply(if idx > 0 { Event::Back } else { Event::Cancel });
+            }
+
+            ui.with_layout(trailing(ui), |ui| {
+                let has = self.has_selection();
+                let is_last = idx == total - 1;
+                let txt = match (is_last, self.skips_review()) {
+                    (false, _) => "Continue",
+                    (true, false) => "Review",
+                    (true, true) => "Submit",
+                };
+
+                let btn_color = if has { self.theme.accent } else { self.theme.surface_hover };
+                let text_color = if has { self.theme.text_inverse } else { self.theme.text_muted };
+
+                // Button with custom arrow icon
+                let btn_resp = egui::Frame::new()
+                    .fill(btn_color)
+                    .corner_radius(8)
+                    .inner_margin(egui::Margin::symmetric(16, 10))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(txt).color(text_color).size(self.theme.size_body));
+                            ui.add_space(6.0);
+                            // Draw arrow icon, pointing the way the text reads
+                            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                            let c = rect.center();
+                            let x = if self.rtl { -1.0 } else { 1.0 };
+                            let stroke = egui::Stroke::new(1.5, text_color);
+                            ui.painter().line_segment([c + egui::vec2(-4.0 * x, 0.0), c + egui::vec2(3.0 * x, 0.0)], stroke);
+                            ui.painter().line_segment([c + egui::vec2(0.0, -3.0), c + egui::vec2(3.0 * x, 0.0)], stroke);
+                            ui.painter().line_segment([c + egui::vec2(0.0, 3.0), c + egui::vec2(3.0 * x, 0.0)], stroke);
+                        });
+                    });
+
+                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("next_btn"), egui::Sense::click());
+                if has && btn_click.clicked() {
+                    self.apply(Event::Next);
+                }
+            });
+        });
+    }
+
+    /// Collapsed to an "Add a note" link until opened or filled in.
+    fn render_note(&mut self, ui: &mut egui::Ui) {
+        if !self.note_open {
+            self.note_focused = false;
+            let link = ui.add(egui::Label::new(egui::RichText::new("+ Add a note")
+                .color(self.theme.text_muted)
+                .size(self.theme.size_caption)).sense(egui::Sense::click()));
+            if link.clicked() {
+                self.note_open = true;
+                ui.memory_mut(|m| m.request_focus(egui::Id::new("note")));
+            }
+            return;
+        }
+
+        let mut text = self.note.clone();
+        let resp = egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, if self.note_focused { self.theme.accent } else { self.theme.border_subtle }))
+            .corner_radius(8)
+            .inner_margin(egui::Margin::symmetric(12, 8))
+            .show(ui, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut text)
+                    .id(egui::Id::new("note"))
+                    .hint_text("Add a caveat or comment...")
+                    .desired_width(ui.available_width())
+                    .text_color(self.theme.text_primary)
+                    .horizontal_align(text_align(self.rtl))
+                    .frame(false))
+            }).inner;
+        self.note_focused = resp.has_focus();
+        if resp.changed() {
+            self.apply(Event::SetNote { text });
+        }
+    }
+
+    fn render_custom_input(&mut self, ui: &mut egui::Ui, q: &Question, dt: f32) {
+        // Custom input with refined styling
+        let custom_idx = q.options.len();
+        let custom_hover_t = self.hover_states.get(custom_idx).map(|s| s.value()).unwrap_or(0.0);
+        let has_custom = self.other;
+
+        let custom_bg = if has_custom || self.custom_focused {
+            Self::lerp_color(self.theme.surface_active, self.theme.accent_muted, 0.2)
+        } else {
+            Self::lerp_color(self.theme.surface, self.theme.surface_hover, custom_hover_t)
+        };
+
+        let custom_border = if has_custom || self.custom_focused {
+            self.theme.accent
+        } else {
+            Self::lerp_color(self.theme.border_subtle, self.theme.border, custom_hover_t)
+        };
+
+        let card = egui::Frame::new()
+            .fill(custom_bg)
+            .stroke(egui::Stroke::new(1.0, custom_border))
+            .corner_radius(10)
+            .inner_margin(egui::Margin::symmetric(14, 10))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    self.draw_indicator(ui, has_custom, q.multi_select, custom_hover_t);
+                    ui.add_space(12.0);
+
+                    column(ui, |ui| {
+                        ui.spacing_mut().item_spacing.y = 4.0;
+                        ui.label(egui::RichText::new("Other")
+                            .color(if has_custom { self.theme.text_primary } else { self.theme.text_secondary })
+                            .size(self.theme.size_option));
+
+                        let mut text = self.custom.clone();
+                        let te = egui::TextEdit::singleline(&mut text)
+                            .hint_text("Type a custom response...")
+                            .desired_width(ui.available_width())
+                            .text_color(self.theme.text_primary)
+                            .horizontal_align(text_align(self.rtl))
+                            .frame(false);
+                        let te_resp = ui.add(te);
+                        self.custom_focused = te_resp.has_focus();
+
+                        if te_resp.changed() {
+                            self.apply(Event::SetCustom { text });
+                        }
+                    });
+                });
+            });
+
+        // Clicking the card outside the text field picks Other
+        let click = ui.interact(card.response.rect, ui.id().with("other"), egui::Sense::click());
+        if click.clicked() && (q.multi_select || !self.other) {
+            self.apply(Event::ToggleOther);
+        }
+
+        // Update custom hover state
+        if let Some(state) = self.hover_states.get_mut(custom_idx) {
+            state.update(self.custom_focused || click.hovered(), dt);
+        }
+    }
+
+    /// Text field plus a browser over `browse_dir`. The path lives in
+    /// `custom`, so typing and picking both go through `SetCustom`.
+    fn render_path_picker(&mut self, ui: &mut egui::Ui, q: &Question) {
+        if self.browse_loaded.as_ref() != Some(&self.browse_dir) {
+            self.browse_entries = pathpick::list_dir(q, &self.browse_dir);
+            self.browse_loaded = Some(self.browse_dir.clone());
+        }
+
+        let check = self.path_check.check(q, &self.custom);
+        let hint = match q.mode {
+            PathMode::File => "Type a file path or pick one below...",
+            PathMode::Directory => "Type a folder path or pick one below...",
+            PathMode::Save => "Type a file name or pick a folder below...",
+        };
+        if self.render_answer_field(ui, hint, &check) {
+            // Follow typed folders in the browser
+            let typed = pathpick::resolve(q, &self.custom);
+            let dir = if typed.is_dir() { Some(typed) } else { typed.parent().map(PathBuf::from) };
+            if let Some(dir) = dir.filter(|d| d.is_dir()) {
+                self.browse_dir = dir;
+            }
+        }
+
+        ui.add_space(8.0);
+
+        // Current folder, with a way up and (for folder questions) a way to pick it
+        ui.horizontal(|ui| {
+            let parent = self.browse_dir.parent().map(PathBuf::from);
+            let up = ui.add_enabled(parent.is_some(), egui::Button::new(
+                egui::RichText::new("Up").color(self.theme.text_secondary).size(self.theme.size_caption))
+                .fill(self.theme.surface)
+                .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+                .corner_radius(6));
+            if let Some(parent) = parent.filter(|_| up.clicked()) {
+                self.browse_dir = parent;
+            }
+            if q.mode == PathMode::Directory {
+                ui.with_layout(trailing(ui), |ui| {
+                    let pick = ui.add(egui::Button::new(
+                        egui::RichText::new("Use this folder").color(self.theme.accent_hover).size(self.theme.size_caption))
+                        .fill(self.theme.accent_muted)
+                        .stroke(egui::Stroke::NONE)
+                        .corner_radius(6));
+                    if pick.clicked() {
+                        self.apply(Event::SetCustom { text: pathpick::display(q, &self.browse_dir) });
+                    }
+                    ui.add(egui::Label::new(egui::RichText::new(bidi::visual(&self.browse_dir.display().to_string()))
+                        .color(self.theme.text_muted)
+                        .size(self.theme.size_caption)).truncate());
+                });
+            } else {
+                ui.add(egui::Label::new(egui::RichText::new(bidi::visual(&self.browse_dir.display().to_string()))
+                    .color(self.theme.text_muted)
+                    .size(self.theme.size_caption)).truncate());
+            }
+        });
+
+        ui.add_space(6.0);
+
+        if self.browse_entries.is_empty() {
+            ui.label(egui::RichText::new(if q.filter.is_some() { "No matching files here" } else { "This folder is empty" })
+                .color(self.theme.text_muted)
+                .size(self.theme.size_body));
+            return;
+        }
+
+        let picked = check.ok().map(|p| p.absolute);
+        let mut clicked = None;
+        ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.y = 2.0;
+            egui::ScrollArea::vertical()
+                .id_salt(&self.browse_dir)
+                .max_height(200.0)
+                .show_rows(ui, LIST_ROW_HEIGHT, self.browse_entries.len(), |ui, range| {
+                    for (i, entry) in self.browse_entries.iter().enumerate().skip(range.start).take(range.len()) {
+                        let path = self.browse_dir.join(&entry.name);
+                        let sel = picked.as_ref() == Some(&entry.canonical);
+                        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), LIST_ROW_HEIGHT), egui::Sense::hover());
+                        let hovered = ui.rect_contains_pointer(rect);
+                        let bg = if sel {
+                            Self::lerp_color(self.theme.surface_active, self.theme.accent_muted, 0.3)
+                        } else if hovered {
+                            self.theme.surface_hover
+                        } else {
+                            self.theme.surface
+                        };
+                        ui.painter().rect_filled(rect, 6.0, bg);
+
+                        let name = if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() };
+                        let color = if sel || hovered {
+                            self.theme.text_primary
+                        } else if entry.is_dir {
+                            self.theme.text_secondary
+                        } else {
+                            self.theme.text_muted
+                        };
+                        let (anchor, align) = if self.rtl {
+                            (rect.right_center() - egui::vec2(10.0, 0.0), egui::Align2::RIGHT_CENTER)
+                        } else {
+                            (rect.left_center() + egui::vec2(10.0, 0.0), egui::Align2::LEFT_CENTER)
+                        };
+                        ui.painter().text(
+                            anchor,
+                            align,
+                            bidi::visual(&name),
+                            egui::FontId::proportional(self.theme.size_option),
+                            color,
+                        );
+
+                        let click = ui.interact(rect, ui.id().with(("entry", i)), egui::Sense::click());
+                        if click.clicked() || click.double_clicked() {
+                            clicked = Some((path, entry.is_dir, click.double_clicked()));
+                        }
+                    }
+                });
+        });
+
+        // Folders open on click; files (and folders, for folder questions)
+        // fill the field. Double-clicking a valid pick moves on.
+        if let Some((path, is_dir, double)) = clicked {
+            if is_dir {
+                self.browse_dir = path.clone();
+            }
+            if !is_dir || q.mode == PathMode::Directory {
+                self.apply(Event::SetCustom { text: pathpick::display(q, &path) });
+                if double && !is_dir && self.has_selection() {
+                    self.apply(Event::Next);
+                }
+            }
+        }
+    }
+
+    /// The typed answer to path, date, time and duration questions, with
+    /// why it isn't accepted yet underneath. True when the text changed.
+    fn render_answer_field<T>(&mut self, ui: &mut egui::Ui, hint: &str, check: &Result<T, String>) -> bool {
+        let mut text = self.custom.clone();
+        let resp = egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, if self.custom_focused || check.is_ok() { self.theme.accent } else { self.theme.border_subtle }))
+            .corner_radius(8)
+            .inner_margin(egui::Margin::symmetric(12, 8))
+            .show(ui, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut text)
+                    .hint_text(hint)
+                    .desired_width(ui.available_width())
+                    .text_color(self.theme.text_primary)
+                    .horizontal_align(text_align(self.rtl))
+                    .frame(false))
+            }).inner;
+        self.custom_focused = resp.has_focus();
+        if resp.changed() {
+            self.apply(Event::SetCustom { text });
+        }
+        match check {
+            Err(msg) if !msg.is_empty() => {
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new(msg).color(self.theme.text_muted).size(self.theme.size_caption));
+            }
+            _ => {}
+        }
+        resp.changed()
+    }
+
+    /// A masked field with a toggle to show what was typed.
+    fn render_secret(&mut self, ui: &mut egui::Ui) {
+        // The field edits a copy, wiped whether or not it's kept
+        let mut text = zeroize::Zeroizing::new(self.custom.clone());
+        let output = egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, if self.custom_focused || !self.custom.is_empty() { self.theme.accent } else { self.theme.border_subtle }))
+            .corner_radius(8)
+            .inner_margin(egui::Margin::symmetric(12, 8))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| ui.with_layout(trailing(ui), |ui| {
+                    let toggle = ui.add(egui::Button::new(
+                        egui::RichText::new(if self.reveal { "Hide" } else { "Show" })
+                            .color(self.theme.text_secondary)
+                            .size(self.theme.size_caption))
+                        .fill(self.theme.surface)
+                        .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+                        .corner_radius(6));
+                    if toggle.clicked() {
+                        self.reveal = !self.reveal;
+                    }
+                    egui::TextEdit::singleline(&mut *text)
+                        .password(!self.reveal)
+                        .hint_text("Type or paste it here...")
+                        .desired_width(ui.available_width())
+                        .text_color(self.theme.text_primary)
+                        .horizontal_align(text_align(self.rtl))
+                        .frame(false)
+                        .show(ui)
+                }).inner).inner
+            }).inner;
+        // Undo history would keep every version of the text
+        let mut state = output.state;
+        state.set_undoer(Default::default());
+        state.store(ui.ctx(), output.response.id);
+
+        self.custom_focused = output.response.has_focus();
+        if output.response.changed() {
+            self.apply(Event::SetCustom { text: std::mem::take(&mut *text) });
+        }
+    }
+
+    /// A text field plus a calendar, a clock or duration fields that fill
+    /// it in.
+    fn render_when(&mut self, ui: &mut egui::Ui, q: &Question) {
+        let check = when::check(q, &self.custom);
+        let hint = match q.kind {
+            QuestionKind::Date => "YYYY-MM-DD",
+            QuestionKind::Time => "HH:MM",
+            QuestionKind::DateTime => "YYYY-MM-DD HH:MM",
+            _ => "e.g. 1h 30m",
+        };
+        if self.render_answer_field(ui, hint, &check) {
+            // Follow a typed date in the calendar
+            if let Some(day) = when::check(q, &self.custom).ok().as_ref().and_then(when::day) {
+                self.calendar = day.first_of_month();
+            }
+        }
+        ui.add_space(10.0);
+
+        // The pickers start from what's typed, else the default or now
+        let chosen = check.as_ref().ok().and_then(when::day);
+        let picked = match check.unwrap_or_else(|_| when::start(q)) {
+            When::Date(_) => self.render_calendar(ui, q, chosen).map(When::Date),
+            When::Time(time) => self.render_clock(ui, time, None).map(When::Time),
+            When::DateTime(at) => {
+                let day = self.render_calendar(ui, q, chosen);
+                ui.add_space(8.0);
+                let time = self.render_clock(ui, at.time(), Some(when::zone_label(q, at)));
+                (day.is_some() || time.is_some()).then(|| {
+                    When::DateTime(day.unwrap_or(at.date()).to_datetime(time.unwrap_or(at.time())))
+                })
+            }
+            When::Duration(secs) => self.render_duration(ui, secs).map(When::Duration),
+        };
+        if let Some(value) = picked {
+            self.apply(Event::SetCustom { text: when::text(&value) });
+        }
+    }
+
+    /// A month of days to click, outside `min` and `max` greyed out.
+    /// Returns the day clicked.
+    fn render_calendar(&mut self, ui: &mut egui::Ui, q: &Question, chosen: Option<jiff::civil::Date>) -> Option<jiff::civil::Date> {
+        let (min, max) = when::bounds(q);
+        let (min, max) = (min.as_ref().and_then(when::day), max.as_ref().and_then(when::day));
+        let today = jiff::Zoned::now().with_time_zone(when::zone(q)).date();
+        let cell = egui::vec2((ui.available_width() / 7.0).min(40.0), 28.0);
+        let width = cell.x * 7.0;
+        // Days run right to left in right-to-left layouts
+        let (x, dir) = if self.rtl { (ui.max_rect().right() - cell.x, -1.0) } else { (ui.max_rect().left(), 1.0) };
+
+        // Month, between arrows to the months either side
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(width, cell.y), egui::Sense::hover());
+        let rect = egui::Rect::from_min_size(egui::pos2(x.min(x + dir * (width - cell.x)), rect.top()), rect.size());
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            self.calendar.strftime("%B %Y").to_string(),
+            egui::FontId::proportional(self.theme.size_body),
+            self.theme.text_primary,
+        );
+        for (step, side) in [(-1, -dir), (1, dir)] {
+            let center = rect.center() + egui::vec2(side * (width / 2.0 - cell.y / 2.0), 0.0);
+            let arrow = egui::Rect::from_center_size(center, egui::vec2(cell.y, cell.y));
+            let click = ui.interact(arrow, ui.id().with(("month", step)), egui::Sense::click());
+            if click.hovered() {
+                ui.painter().rect_filled(arrow, 6.0, self.theme.surface_hover);
+            }
+            let c = arrow.center();
+            let stroke = egui::Stroke::new(1.5, self.theme.text_secondary);
+            ui.painter().line_segment([c + egui::vec2(-2.0 * side, -4.0), c + egui::vec2(2.0 * side, 0.0)], stroke);
+            ui.painter().line_segment([c + egui::vec2(-2.0 * side, 4.0), c + egui::vec2(2.0 * side, 0.0)], stroke);
+            if click.clicked() {
+                if let Ok(month) = self.calendar.checked_add(jiff::Span::new().months(step)) {
+                    self.calendar = month;
+                }
+            }
+        }
+
+        let cell_rect = |top: f32, column: usize| {
+            egui::Rect::from_min_size(egui::pos2(x + dir * cell.x * column as f32, top), cell)
+        };
+        let (row, _) = ui.allocate_exact_size(egui::vec2(width, cell.y), egui::Sense::hover());
+        for (column, name) in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"].iter().enumerate() {
+            let rect = cell_rect(row.top(), column);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                *name,
+                egui::FontId::proportional(self.theme.size_caption),
+                self.theme.text_muted,
+            );
+        }
+
+        let mut clicked = None;
+        let offset = self.calendar.weekday().to_monday_zero_offset() as usize;
+        let days = self.calendar.days_in_month() as usize;
+        for week in 0..(offset + days).div_ceil(7) {
+            let (row, _) = ui.allocate_exact_size(egui::vec2(width, cell.y), egui::Sense::hover());
+            for column in 0..7 {
+                let Some(n) = (week * 7 + column).checked_sub(offset).filter(|&n| n < days) else { continue };
+                let Ok(day) = self.calendar.checked_add(jiff::Span::new().days(n as i64)) else { continue };
+                let rect = cell_rect(row.top(), column).shrink(2.0);
+                let enabled = min.is_none_or(|m| day >= m) && max.is_none_or(|m| day <= m);
+                let click = ui.interact(rect, ui.id().with(("day", n)), if enabled { egui::Sense::click() } else { egui::Sense::hover() });
+                let selected = chosen == Some(day);
+                let (fill, color) = if selected {
+                    (self.theme.accent, self.theme.text_inverse)
+                } else if !enabled {
+                    (egui::Color32::TRANSPARENT, self.theme.border)
+                } else if click.hovered() {
+                    (self.theme.surface_hover, self.theme.text_primary)
+                } else {
+                    (egui::Color32::TRANSPARENT, self.theme.text_secondary)
+                };
+                ui.painter().rect_filled(rect, 6.0, fill);
+                if day == today && !selected {
+                    ui.painter().rect_stroke(rect, 6.0, egui::Stroke::new(1.0, self.theme.accent), egui::StrokeKind::Inside);
+                }
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    day.day().to_string(),
+                    egui::FontId::proportional(self.theme.size_body),
+                    color,
+                );
+                if click.clicked() {
+                    clicked = Some(day);
+                }
+            }
+        }
+        clicked
+    }
+
+    /// Hour and minute fields, with `zone` beside them. Returns the time
+    /// when either was changed.
+    fn render_clock(&mut self, ui: &mut egui::Ui, time: jiff::civil::Time, zone: Option<String>) -> Option<jiff::civil::Time> {
+        let (mut hour, mut minute) = (time.hour(), time.minute());
+        let changed = ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            let hours = self.number_field(ui, &mut hour, 0..=23, "");
+            ui.label(egui::RichText::new(":").color(self.theme.text_secondary).size(self.theme.size_option));
+            let minutes = self.number_field(ui, &mut minute, 0..=59, "");
+            if let Some(zone) = zone {
+                ui.add_space(8.0);
+                bidi::label(ui, egui::RichText::new(zone).color(self.theme.text_muted).size(self.theme.size_caption));
+            }
+            hours || minutes
+        }).inner;
+        changed.then(|| jiff::civil::Time::new(hour, minute, time.second(), 0).unwrap_or(time))
+    }
+
+    /// Day, hour, minute and second fields. Returns the new length in
+    /// seconds when one was changed.
+    fn render_duration(&mut self, ui: &mut egui::Ui, secs: i64) -> Option<i64> {
+        let mut units = when::units(secs);
+        let changed = ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            let mut changed = false;
+            for (n, (unit, max)) in units.iter_mut().zip([("d", 365), ("h", 23), ("m", 59), ("s", 59)]) {
+                changed |= self.number_field(ui, n, 0..=max, unit);
+                ui.add_space(6.0);
+            }
+            changed
+        }).inner;
+        let [d, h, m, s] = units;
+        changed.then_some(((d * 24 + h) * 60 + m) * 60 + s)
+    }
+
+    /// A number to drag, type or step with the arrow keys, two digits wide.
+    fn number_field<N: egui::emath::Numeric>(&self, ui: &mut egui::Ui, n: &mut N, range: RangeInclusive<N>, unit: &str) -> bool {
+        egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+            .corner_radius(6)
+            .inner_margin(egui::Margin::symmetric(6, 4))
+            .show(ui, |ui| {
+                ui.add(egui::DragValue::new(n)
+                    .range(range)
+                    .speed(0.1)
+                    .suffix(unit)
+                    .custom_formatter(|v, _| format!("{:02}", v as i64)))
+            }).inner.changed()
+    }
+
+    fn render_option_cards(&mut self, ui: &mut egui::Ui, q: &Question, dt: f32) {
         // Options with smooth hover animations
         egui::ScrollArea::vertical()
             .max_height(200.0)
@@ -435,6 +2585,7 @@ impl App {
                         Self::lerp_color(self.theme.border_subtle, self.theme.border, hover_t)
                     };
 
+                    let mut thumbnail = None;
                     let resp = egui::Frame::new()
                         .fill(bg)
                         .stroke(egui::Stroke::new(1.0, border_color))
@@ -445,148 +2596,371 @@ impl App {
                             ui.horizontal(|ui| {
                                 self.draw_indicator(ui, sel, q.multi_select, hover_t);
                                 ui.add_space(12.0);
-                                ui.vertical(|ui| {
+                                let text_width = ui.available_width() - if opt.image.is_some() { THUMBNAIL.x + 12.0 } else { 0.0 };
+                                column(ui, |ui| {
+                                    ui.set_max_width(text_width);
                                     ui.spacing_mut().item_spacing.y = 2.0;
                                     let text_color = if sel {
                                         self.theme.text_primary
                                     } else {
                                         Self::lerp_color(self.theme.text_secondary, self.theme.text_primary, hover_t)
                                     };
-                                    ui.label(egui::RichText::new(&opt.label)
-                                        .color(text_color)
-                                        .size(13.0));
+                                    ui.horizontal(|ui| {
+                                        bidi::label(ui, egui::RichText::new(&opt.label)
+                                            .color(text_color)
+                                            .size(self.theme.size_option));
+                                        if opt.recommended {
+                                            self.draw_badge(ui, "Recommended");
+                                        }
+                                    });
                                     if !opt.description.is_empty() {
-                                        ui.label(egui::RichText::new(&opt.description)
+                                        bidi::label(ui, egui::RichText::new(&opt.description)
                                             .color(self.theme.text_muted)
-                                            .size(11.0));
+                                            .size(self.theme.size_caption));
                                     }
                                 });
+                                if let Some(source) = &opt.image {
+                                    ui.with_layout(trailing(ui), |ui| {
+                                        thumbnail = Some(self.draw_image(ui, source, THUMBNAIL));
+                                    });
+                                }
                             });
                         });
 
                     let click_resp = ui.interact(resp.response.rect, id, egui::Sense::click());
                     if click_resp.clicked() {
-                        if q.multi_select {
-                            if let Some(s) = self.selected.get_mut(i) { *s = !*s; }
-                        } else {
-                            self.selected.iter_mut().enumerate().for_each(|(j, s)| *s = j == i);
-                            self.custom.clear();
+                        self.pick(i);
+                    }
+                    // Added after the card so a click on the picture zooms
+                    // rather than picks
+                    if let (Some(rect), Some(source)) = (thumbnail, &opt.image) {
+                        self.zoom_on_click(ui, rect, source);
+                    }
+                }
+            });
+    }
+
+    /// One row per item and one column per option, a radio button or
+    /// checkbox in each cell.
+    fn render_matrix(&mut self, ui: &mut egui::Ui, q: &Question) {
+        let columns = q.options.len().max(1) as f32;
+        let width = ui.available_width();
+        let cell_width = (width * 0.6 / columns).clamp(44.0, 96.0);
+        let label_width = (width - cell_width * columns).max(60.0);
+        // Which way along the row cells go, from the edge labels sit on
+        let (x, dir) = if self.rtl { (ui.max_rect().right(), -1.0) } else { (ui.max_rect().left(), 1.0) };
+        let cell_x = |c: usize| x + dir * (label_width + cell_width * (c as f32 + 0.5));
+        let font = egui::FontId::proportional(self.theme.size_caption);
+        let option_font = egui::FontId::proportional(self.theme.size_option);
+
+        // Column headings, wrapped to their cells and aligned at the bottom
+        let headings: Vec<_> = q.options.iter()
+            .map(|o| ui.painter().layout(bidi::visual(&o.label), font.clone(), self.theme.text_muted, cell_width - 6.0))
+            .collect();
+        let height = headings.iter().map(|g| g.size().y).fold(0.0, f32::max);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height + 6.0), egui::Sense::hover());
+        for (c, galley) in headings.into_iter().enumerate() {
+            let pos = egui::pos2(cell_x(c) - galley.size().x / 2.0, rect.top() + height - galley.size().y);
+            ui.painter().galley(pos, galley, self.theme.text_muted);
+        }
+
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .show(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 4.0;
+                for (r, row) in q.rows.iter().enumerate() {
+                    let galley = ui.painter().layout(bidi::visual(&row.label), option_font.clone(), self.theme.text_secondary, label_width - 20.0);
+                    let size = egui::vec2(width, (galley.size().y + 14.0).max(36.0));
+                    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+                    let answered = self.row_answered(r);
+                    let hovered = ui.rect_contains_pointer(rect);
+                    let bg = if hovered { self.theme.surface_hover } else { self.theme.surface };
+                    ui.painter().rect(
+                        rect,
+                        8,
+                        bg,
+                        egui::Stroke::new(1.0, if answered { self.theme.border } else { self.theme.border_subtle }),
+                        egui::StrokeKind::Inside,
+                    );
+                    let color = if answered || hovered { self.theme.text_primary } else { self.theme.text_secondary };
+                    let text_x = if self.rtl { rect.right() - 12.0 - galley.size().x } else { rect.left() + 12.0 };
+                    ui.painter().galley(egui::pos2(text_x, rect.center().y - galley.size().y / 2.0), galley, color);
+                    if !row.description.is_empty() {
+                        response.on_hover_text(&row.description);
+                    }
+
+                    for c in 0..q.options.len() {
+                        let center = egui::pos2(cell_x(c), rect.center().y);
+                        let cell = egui::Rect::from_center_size(center, egui::vec2(cell_width, rect.height()));
+                        let click = ui.interact(cell, ui.id().with(("cell", r, c)), egui::Sense::click());
+                        let selected = self.selected.get(q.cell(r, c)).copied().unwrap_or(false);
+                        let mut indicator = ui.new_child(egui::UiBuilder::new()
+                            .max_rect(egui::Rect::from_center_size(center, egui::vec2(18.0, 18.0))));
+                        self.draw_indicator(&mut indicator, selected, q.multi_select, if click.hovered() { 1.0 } else { 0.0 });
+                        if click.clicked() {
+                            self.apply(Event::ToggleCell { row: r, column: c });
                         }
                     }
                 }
             });
+    }
 
-        ui.add_space(10.0);
+    /// Options matching the filter, best match first. With no filter, all
+    /// options in order, grouped by section.
+    fn filtered_options(&self, q: &Question) -> Vec<usize> {
+        if self.filter.trim().is_empty() {
+            let mut groups: Vec<Option<&String>> = Vec::new();
+            for o in &q.options {
+                if !groups.contains(&o.group.as_ref()) {
+                    groups.push(o.group.as_ref());
+                }
+            }
+            return groups.into_iter()
+                .flat_map(|g| (0..q.options.len()).filter(move |&i| q.options[i].group.as_ref() == g))
+                .collect();
+        }
 
-        // Custom input with refined styling
-        let custom_idx = q.options.len();
-        let custom_hover_t = self.hover_states.get(custom_idx).map(|s| s.value()).unwrap_or(0.0);
-        let has_custom = !self.custom.trim().is_empty();
+        let mut scored: Vec<(i32, usize)> = q.options.iter().enumerate().filter_map(|(i, o)| {
+            let label = fuzzy::score(&self.filter, &o.label).map(|s| s + 10);
+            let description = fuzzy::score(&self.filter, &o.description);
+            label.max(description).map(|s| (s, i))
+        }).collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
 
-        let custom_bg = if has_custom || self.custom_focused {
-            Self::lerp_color(self.theme.surface_active, self.theme.accent_muted, 0.2)
-        } else {
-            Self::lerp_color(self.theme.surface, self.theme.surface_hover, custom_hover_t)
-        };
+    /// Large option sets: a filter box over compact rows grouped under
+    /// headings. Only the rows in view are laid out.
+    fn render_option_list(&mut self, ui: &mut egui::Ui, q: &Question) {
+        let filter_resp = egui::Frame::new()
+            .fill(self.theme.surface)
+            .stroke(egui::Stroke::new(1.0, if self.filter_focused { self.theme.accent } else { self.theme.border_subtle }))
+            .corner_radius(8)
+            .inner_margin(egui::Margin::symmetric(12, 8))
+            .show(ui, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text(format!("Filter {} options...", q.options.len()))
+                    .desired_width(ui.available_width())
+                    .text_color(self.theme.text_primary)
+                    .horizontal_align(text_align(self.rtl))
+                    .frame(false))
+            }).inner;
+        if filter_resp.changed() {
+            self.list_cursor = 0;
+            self.list_scroll_to = Some(0.0);
+        }
+
+        let matches = self.filtered_options(q);
+        self.list_cursor = self.list_cursor.min(matches.len().saturating_sub(1));
+
+        // Enter in the filter picks the highlighted (by default the top) match
+        // and keeps the filter focused for the next one
+        let enter = ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if filter_resp.lost_focus() && enter {
+            if let Some(&i) = matches.get(self.list_cursor) {
+                self.pick(i);
+            }
+            filter_resp.request_focus();
+            self.filter_focused = true;
+        } else {
+            self.filter_focused = filter_resp.has_focus();
+        }
 
-        let custom_border = if has_custom || self.custom_focused {
-            self.theme.accent
+        let rows: Vec<ListRow> = if self.filter.trim().is_empty() && q.options.iter().any(|o| o.group.is_some()) {
+            let mut rows = Vec::new();
+            let mut current = None;
+            for &i in &matches {
+                let group = q.options[i].group.as_ref();
+                if group != current {
+                    rows.push(ListRow::Heading(group.cloned().unwrap_or_default()));
+                    current = group;
+                }
+                rows.push(ListRow::Option(i));
+            }
+            rows
         } else {
-            Self::lerp_color(self.theme.border_subtle, self.theme.border, custom_hover_t)
+            matches.iter().map(|&i| ListRow::Option(i)).collect()
         };
 
-        egui::Frame::new()
-            .fill(custom_bg)
-            .stroke(egui::Stroke::new(1.0, custom_border))
-            .corner_radius(10)
-            .inner_margin(egui::Margin::symmetric(14, 10))
-            .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    if q.multi_select {
-                        self.draw_indicator(ui, has_custom, true, custom_hover_t);
-                        ui.add_space(12.0);
-                    }
+        // Arrow keys move the highlight, scrolling it into view
+        if !self.custom_focused && !matches.is_empty() {
+            let (up, down) = ui.input(|i| (i.key_pressed(egui::Key::ArrowUp), i.key_pressed(egui::Key::ArrowDown)));
+            if up || down {
+                self.list_cursor = if down {
+                    (self.list_cursor + 1).min(matches.len() - 1)
+                } else {
+                    self.list_cursor.saturating_sub(1)
+                };
+                let target = matches[self.list_cursor];
+                let row = rows.iter().position(|r| matches!(r, ListRow::Option(i) if *i == target)).unwrap_or(0);
+                let row_h = LIST_ROW_HEIGHT + 2.0;
+                let top = row as f32 * row_h;
+                if top < self.list_scroll {
+                    self.list_scroll_to = Some(top);
+                } else if top + row_h > self.list_scroll + self.list_view_height {
+                    self.list_scroll_to = Some(top + row_h - self.list_view_height);
+                }
+            }
+        }
 
-                    ui.vertical(|ui| {
-                        ui.spacing_mut().item_spacing.y = 4.0;
-                        ui.label(egui::RichText::new("Other")
-                            .color(if has_custom { self.theme.text_primary } else { self.theme.text_secondary })
-                            .size(13.0));
+        ui.add_space(8.0);
 
-                        let te = egui::TextEdit::singleline(&mut self.custom)
-                            .hint_text("Type a custom response...")
-                            .desired_width(ui.available_width())
-                            .text_color(self.theme.text_primary)
-                            .frame(false);
-                        let te_resp = ui.add(te);
-                        self.custom_focused = te_resp.has_focus();
+        if matches.is_empty() {
+            ui.label(egui::RichText::new("No matching options")
+                .color(self.theme.text_muted)
+                .size(self.theme.size_body));
+            return;
+        }
 
-                        // Clear predefined selection when typing custom (single select)
-                        if !q.multi_select && te_resp.changed() && !self.custom.is_empty() {
-                            self.selected.iter_mut().for_each(|s| *s = false);
+        let highlighted = matches.get(self.list_cursor).copied();
+        ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.y = 2.0;
+            let mut area = egui::ScrollArea::vertical().max_height(240.0);
+            if let Some(offset) = self.list_scroll_to.take() {
+                area = area.vertical_scroll_offset(offset);
+            }
+            let output = area.show_rows(ui, LIST_ROW_HEIGHT, rows.len(), |ui, range| {
+                for row in &rows[range] {
+                    match row {
+                        ListRow::Heading(name) => {
+                            let (rect, _) = ui.allocate_exact_size(
+                                egui::vec2(ui.available_width(), LIST_ROW_HEIGHT),
+                                egui::Sense::hover()
+                            );
+                            let (anchor, align) = if self.rtl {
+                                (rect.right_bottom() + egui::vec2(-4.0, -7.0), egui::Align2::RIGHT_BOTTOM)
+                            } else {
+                                (rect.left_bottom() + egui::vec2(4.0, -7.0), egui::Align2::LEFT_BOTTOM)
+                            };
+                            ui.painter().text(
+                                anchor,
+                                align,
+                                bidi::visual(&name.to_uppercase()),
+                                egui::FontId::proportional(self.theme.size_label),
+                                self.theme.text_muted,
+                            );
                         }
-                    });
-                });
+                        ListRow::Option(i) => self.render_list_row(ui, q, *i, highlighted == Some(*i)),
+                    }
+                }
             });
+            self.list_scroll = output.state.offset.y;
+            self.list_view_height = output.inner_rect.height();
+        });
+    }
 
-        // Update custom hover state
-        if let Some(state) = self.hover_states.get_mut(custom_idx) {
-            state.update(self.custom_focused || has_custom, dt);
-        }
+    fn render_list_row(&mut self, ui: &mut egui::Ui, q: &Question, i: usize, highlighted: bool) {
+        let opt = &q.options[i];
+        let sel = self.selected.get(i).copied().unwrap_or(false);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), LIST_ROW_HEIGHT), egui::Sense::hover());
+        let hovered = ui.rect_contains_pointer(rect);
 
-        ui.add_space(20.0);
+        let bg = if sel {
+            Self::lerp_color(self.theme.surface_active, self.theme.accent_muted, 0.3)
+        } else if hovered || highlighted {
+            self.theme.surface_hover
+        } else {
+            self.theme.surface
+        };
+        ui.painter().rect_filled(rect, 6.0, bg);
+        if highlighted {
+            ui.painter().rect_stroke(rect, 6.0, egui::Stroke::new(1.0, self.theme.border), egui::StrokeKind::Inside);
+        }
 
-        // Footer buttons
-        ui.horizontal(|ui| {
-            // Back/Cancel button
-            let back_text = if idx > 0 { "Back" } else { "Cancel" };
-            let back_resp = ui.add(
-                egui::Button::new(egui::RichText::new(back_text).color(self.theme.text_muted).size(12.0))
-                    .fill(egui::Color32::TRANSPARENT)
-                    .stroke(egui::Stroke::NONE)
-                    .min_size(egui::vec2(70.0, 38.0))
-            );
-            if back_resp.clicked() {
-                if idx > 0 { self.go_back(); } else { self.cancel(ctx); }
-            }
+        let mut row = ui.new_child(egui::UiBuilder::new()
+            .max_rect(rect.shrink2(egui::vec2(10.0, 0.0)))
+            .layout(if self.rtl {
+                egui::Layout::right_to_left(egui::Align::Center)
+            } else {
+                egui::Layout::left_to_right(egui::Align::Center)
+            }));
+        self.draw_indicator(&mut row, sel, q.multi_select, if hovered { 1.0 } else { 0.0 });
+        row.add_space(10.0);
+        bidi::label(&mut row, egui::RichText::new(&opt.label)
+            .color(if sel || hovered { self.theme.text_primary } else { self.theme.text_secondary })
+            .size(self.theme.size_option));
+        if opt.recommended {
+            self.draw_badge(&mut row, "Recommended");
+        }
+        if !opt.description.is_empty() {
+            row.add_space(6.0);
+            row.add(egui::Label::new(egui::RichText::new(bidi::visual(&opt.description))
+                .color(self.theme.text_muted)
+                .size(self.theme.size_caption)).truncate());
+        }
 
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                let has = self.has_selection();
-                let is_last = idx == total - 1;
-                let txt = if is_last { "Review" } else { "Continue" };
+        let click = ui.interact(rect, ui.id().with(("row", i)), egui::Sense::click());
+        if click.clicked() {
+            self.pick(i);
+        }
+    }
 
-                let btn_color = if has { self.theme.accent } else { self.theme.surface_hover };
-                let text_color = if has { self.theme.text_inverse } else { self.theme.text_muted };
+    /// Draws the picture `source` names within `max`, or a placeholder
+    /// saying why it can't be shown. Returns where it went.
+    fn draw_image(&mut self, ui: &mut egui::Ui, source: &str, max: egui::Vec2) -> egui::Rect {
+        let ctx = ui.ctx().clone();
+        match self.images.get(&ctx, source) {
+            images::Image::Ready(texture) => {
+                let (rect, _) = ui.allocate_exact_size(images::fit(texture, max), egui::Sense::hover());
+                egui::Image::new((texture.id(), rect.size())).corner_radius(6).paint_at(ui, rect);
+                rect
+            }
+            images::Image::Failed(reason) => {
+                let size = egui::vec2(max.x, max.y.min(48.0));
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+                ui.painter().rect(
+                    rect,
+                    6,
+                    self.theme.surface_hover,
+                    egui::Stroke::new(1.0, self.theme.border_subtle),
+                    egui::StrokeKind::Inside,
+                );
+                let (text, font_size) = if size.x > 160.0 {
+                    (format!("Image {}", reason), self.theme.size_caption)
+                } else {
+                    (reason.to_string(), self.theme.size_badge)
+                };
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    text,
+                    egui::FontId::proportional(font_size),
+                    self.theme.text_muted,
+                );
+                let shown: String = source.chars().take(80).collect();
+                response.on_hover_text(format!("Image {}: {}", reason, shown));
+                rect
+            }
+        }
+    }
 
-                // Button with custom arrow icon
-                let btn_resp = egui::Frame::new()
-                    .fill(btn_color)
-                    .corner_radius(8)
-                    .inner_margin(egui::Margin::symmetric(16, 10))
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new(txt).color(text_color).size(12.0));
-                            ui.add_space(6.0);
-                            // Draw arrow icon
-                            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-                            let c = rect.center();
-                            let stroke = egui::Stroke::new(1.5, text_color);
-                            ui.painter().line_segment([c + egui::vec2(-4.0, 0.0), c + egui::vec2(3.0, 0.0)], stroke);
-                            ui.painter().line_segment([c + egui::vec2(0.0, -3.0), c + egui::vec2(3.0, 0.0)], stroke);
-                            ui.painter().line_segment([c + egui::vec2(0.0, 3.0), c + egui::vec2(3.0, 0.0)], stroke);
-                        });
-                    });
+    /// Shows `source` full size when `rect` is clicked.
+    fn zoom_on_click(&mut self, ui: &mut egui::Ui, rect: egui::Rect, source: &str) {
+        if !matches!(self.images.get(ui.ctx(), source), images::Image::Ready(_)) {
+            return;
+        }
+        let response = ui.interact(rect, ui.id().with(("zoom", source)), egui::Sense::click())
+            .on_hover_cursor(egui::CursorIcon::ZoomIn);
+        if response.clicked() {
+            self.zoomed = Some(source.to_string());
+        }
+    }
 
-                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("next_btn"), egui::Sense::click());
-                if has && btn_click.clicked() {
-                    self.go_next();
-                }
+    fn draw_badge(&self, ui: &mut egui::Ui, text: &str) {
+        egui::Frame::new()
+            .fill(self.theme.success_muted)
+            .corner_radius(4)
+            .inner_margin(egui::Margin::symmetric(6, 1))
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(text)
+                    .color(self.theme.success)
+                    .size(self.theme.size_badge)
+                    .strong());
             });
-        });
     }
 
     fn render_review(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        let dt = ctx.input(|i| i.stable_dt);
+        let dt = self.frame_dt(ctx);
         self.transition_progress = (self.transition_progress + dt * 4.0).min(1.0);
         let fade = ease_out_cubic(self.transition_progress);
 
@@ -607,14 +2981,14 @@ impl App {
                         ui.painter().line_segment([c + egui::vec2(-1.0, 2.0), c + egui::vec2(3.0, -2.0)], stroke);
                         ui.label(egui::RichText::new("Complete")
                             .color(self.theme.success)
-                            .size(10.0)
+                            .size(self.theme.size_label)
                             .strong());
                     });
                 });
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.with_layout(trailing(ui), |ui| {
                 ui.label(egui::RichText::new(format!("{} questions", self.answers.len()))
                     .color(self.theme.text_muted)
-                    .size(11.0));
+                    .size(self.theme.size_caption));
             });
         });
 
@@ -635,12 +3009,12 @@ impl App {
                 self.theme.text_primary.b(),
                 alpha
             ))
-            .size(17.0));
+            .size(self.theme.size_title));
 
         ui.add_space(4.0);
         ui.label(egui::RichText::new("Make sure everything looks right before submitting")
             .color(self.theme.text_muted)
-            .size(11.0));
+            .size(self.theme.size_caption));
 
         ui.add_space(18.0);
 
@@ -682,88 +3056,371 @@ impl App {
                                                 self.theme.accent.b(),
                                                 item_alpha
                                             ))
-                                            .size(10.0));
+                                            .size(self.theme.size_label));
                                     });
 
-                                if !answer.header.is_empty() {
-                                    ui.label(egui::RichText::new(&answer.header)
-                                        .color(egui::Color32::from_rgba_unmultiplied(
-                                            self.theme.text_muted.r(),
-                                            self.theme.text_muted.g(),
-                                            self.theme.text_muted.b(),
-                                            item_alpha
-                                        ))
-                                        .size(10.0));
-                                }
-                            });
+                                if !answer.header.is_empty() {
+                                    bidi::label(ui, egui::RichText::new(&answer.header)
+                                        .color(egui::Color32::from_rgba_unmultiplied(
+                                            self.theme.text_muted.r(),
+                                            self.theme.text_muted.g(),
+                                            self.theme.text_muted.b(),
+                                            item_alpha
+                                        ))
+                                        .size(self.theme.size_label));
+                                }
+                            });
+
+                            ui.add_space(8.0);
+
+                            // Selected values as chips; a matrix's rows one per line
+                            let chips = |ui: &mut egui::Ui| {
+                                ui.spacing_mut().item_spacing = egui::vec2(6.0, 6.0);
+                                for sel in answer.selected.iter().chain(&answer.other) {
+                                    let sel = if answer.secret { SECRET_MASK } else { sel };
+                                    egui::Frame::new()
+                                        .fill(egui::Color32::from_rgba_unmultiplied(
+                                            self.theme.success_muted.r(),
+                                            self.theme.success_muted.g(),
+                                            self.theme.success_muted.b(),
+                                            item_alpha
+                                        ))
+                                        .corner_radius(6)
+                                        .inner_margin(egui::Margin::symmetric(10, 5))
+                                        .show(ui, |ui| {
+                                            bidi::label(ui, egui::RichText::new(sel)
+                                                .color(egui::Color32::from_rgba_unmultiplied(
+                                                    self.theme.success.r(),
+                                                    self.theme.success.g(),
+                                                    self.theme.success.b(),
+                                                    item_alpha
+                                                ))
+                                                .size(self.theme.size_body));
+                                        });
+                                }
+                            };
+                            if answer.rows.is_some() {
+                                ui.vertical(chips);
+                            } else {
+                                ui.horizontal_wrapped(chips);
+                            }
+
+                            if let Some(note) = &answer.note {
+                                ui.add_space(6.0);
+                                bidi::label(ui, egui::RichText::new(note)
+                                    .color(egui::Color32::from_rgba_unmultiplied(
+                                        self.theme.text_secondary.r(),
+                                        self.theme.text_secondary.g(),
+                                        self.theme.text_secondary.b(),
+                                        item_alpha
+                                    ))
+                                    .size(self.theme.size_caption)
+                                    .italics());
+                            }
+                        });
+                }
+            });
+
+        ui.add_space(20.0);
+
+        // Footer buttons
+        ui.horizontal(|ui| {
+            let back_resp = ui.add(
+                egui::Button::new(egui::RichText::new("Edit").color(self.theme.text_muted).size(self.theme.size_body))
+                    .fill(egui::Color32::TRANSPARENT)
+                    .stroke(egui::Stroke::NONE)
+                    .min_size(egui::vec2(70.0, 38.0))
+            );
+            if back_resp.clicked() {
+                self.apply(Event::Back);
+            }
+
+            ui.with_layout(trailing(ui), |ui| {
+                // Submit button with custom checkmark icon
+                let btn_resp = egui::Frame::new()
+                    .fill(self.theme.success)
+                    .corner_radius(8)
+                    .inner_margin(egui::Margin::symmetric(16, 10))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Submit").color(self.theme.text_inverse).size(self.theme.size_body));
+                            ui.add_space(6.0);
+                            // Draw checkmark icon
+                            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                            let c = rect.center();
+                            let stroke = egui::Stroke::new(1.8, self.theme.text_inverse);
+                            ui.painter().line_segment([c + egui::vec2(-4.0, 0.0), c + egui::vec2(-1.0, 3.0)], stroke);
+                            ui.painter().line_segment([c + egui::vec2(-1.0, 3.0), c + egui::vec2(4.0, -3.0)], stroke);
+                        });
+                    });
+
+                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("submit_btn"), egui::Sense::click());
+                if btn_click.clicked() {
+                    self.apply(Event::Submit);
+                }
+            });
+        });
+    }
+
+    fn render_restore(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let dt = self.frame_dt(ctx);
+        self.transition_progress = (self.transition_progress + dt * 4.0).min(1.0);
+        let fade = ease_out_cubic(self.transition_progress);
+        let Some(draft) = self.offered_draft.clone() else { return };
+
+        // Header
+        ui.horizontal(|ui| {
+            egui::Frame::new()
+                .fill(self.theme.accent_muted)
+                .corner_radius(4)
+                .inner_margin(egui::Margin::symmetric(8, 3))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Draft")
+                        .color(self.theme.accent_hover)
+                        .size(self.theme.size_label)
+                        .strong());
+                });
+            ui.with_layout(trailing(ui), |ui| {
+                ui.label(egui::RichText::new(format!("{} of {} answered", draft.answers.len(), self.questions.len()))
+                    .color(self.theme.text_muted)
+                    .size(self.theme.size_caption));
+            });
+        });
+
+        ui.add_space(24.0);
+
+        let alpha = (fade * 255.0) as u8;
+        ui.label(egui::RichText::new("Pick up where you left off?")
+            .color(egui::Color32::from_rgba_unmultiplied(
+                self.theme.text_primary.r(),
+                self.theme.text_primary.g(),
+                self.theme.text_primary.b(),
+                alpha
+            ))
+            .size(self.theme.size_title));
+
+        ui.add_space(4.0);
+        ui.label(egui::RichText::new("These questions were closed before you finished answering them")
+            .color(self.theme.text_muted)
+            .size(self.theme.size_caption));
+
+        ui.add_space(18.0);
+
+        // Answers from the draft
+        egui::ScrollArea::vertical()
+            .max_height(220.0)
+            .show(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 8.0;
+                for answer in &draft.answers {
+                    egui::Frame::new()
+                        .fill(self.theme.surface)
+                        .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+                        .corner_radius(10)
+                        .inner_margin(egui::Margin::symmetric(14, 10))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            let title = if answer.header.is_empty() { &answer.question } else { &answer.header };
+                            bidi::label(ui, egui::RichText::new(title)
+                                .color(self.theme.text_muted)
+                                .size(self.theme.size_label));
+                            let shown: Vec<&str> = answer.selected.iter().map(String::as_str).chain(answer.other.as_deref()).collect();
+                            bidi::label(ui, egui::RichText::new(shown.join(", "))
+                                .color(self.theme.text_secondary)
+                                .size(self.theme.size_body));
+                        });
+                }
+            });
+
+        ui.add_space(20.0);
+
+        // Footer buttons
+        ui.horizontal(|ui| {
+            let discard_resp = ui.add(
+                egui::Button::new(egui::RichText::new("Start over").color(self.theme.text_muted).size(self.theme.size_body))
+                    .fill(egui::Color32::TRANSPARENT)
+                    .stroke(egui::Stroke::NONE)
+                    .min_size(egui::vec2(70.0, 38.0))
+            );
+            if discard_resp.clicked() {
+                self.apply(Event::Discard);
+            }
+
+            ui.with_layout(trailing(ui), |ui| {
+                let btn_resp = egui::Frame::new()
+                    .fill(self.theme.accent)
+                    .corner_radius(8)
+                    .inner_margin(egui::Margin::symmetric(16, 10))
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Restore").color(self.theme.text_inverse).size(self.theme.size_body));
+                    });
+
+                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("restore_btn"), egui::Sense::click());
+                if btn_click.clicked() {
+                    self.apply(Event::Restore { draft });
+                }
+            });
+        });
+    }
+
+    fn render_confirm(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let dt = self.frame_dt(ctx);
+        self.transition_progress = (self.transition_progress + dt * 4.0).min(1.0);
+        let fade = ease_out_cubic(self.transition_progress);
+        let Some(confirm) = self.confirm.clone() else { return };
+        self.render_banner(ui);
+
+        let (tone, tone_hover, tone_muted) = if confirm.danger {
+            (self.theme.danger, self.theme.danger_hover, self.theme.danger_muted)
+        } else {
+            (self.theme.accent, self.theme.accent_hover, self.theme.accent_muted)
+        };
+
+        // Header
+        egui::Frame::new()
+            .fill(tone_muted)
+            .corner_radius(4)
+            .inner_margin(egui::Margin::symmetric(8, 3))
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(if confirm.danger { "Destructive" } else { "Confirm" })
+                    .color(tone_hover)
+                    .size(self.theme.size_label)
+                    .strong());
+            });
+
+        ui.add_space(24.0);
+
+        let alpha = (fade * 255.0) as u8;
+        bidi::label(ui, egui::RichText::new(&confirm.title)
+            .color(egui::Color32::from_rgba_unmultiplied(
+                self.theme.text_primary.r(),
+                self.theme.text_primary.g(),
+                self.theme.text_primary.b(),
+                alpha
+            ))
+            .size(self.theme.size_title));
+
+        if !confirm.body.is_empty() {
+            ui.add_space(6.0);
+            bidi::label(ui, egui::RichText::new(&confirm.body)
+                .color(self.theme.text_secondary)
+                .size(self.theme.size_body));
+        }
+
+        ui.add_space(18.0);
 
-                            ui.add_space(8.0);
+        // Command and diff previews
+        if let Some(command) = &confirm.command {
+            egui::Frame::new()
+                .fill(self.theme.surface)
+                .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+                .corner_radius(8)
+                .inner_margin(egui::Margin::symmetric(12, 10))
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    // Code reads left to right whatever the layout
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+                        ui.add(egui::Label::new(egui::RichText::new(format!("$ {}", command))
+                            .color(self.theme.text_primary)
+                            .monospace()
+                            .size(self.theme.size_body)).wrap());
+                    });
+                });
+            ui.add_space(10.0);
+        }
 
-                            // Selected values as chips
-                            ui.horizontal_wrapped(|ui| {
-                                ui.spacing_mut().item_spacing = egui::vec2(6.0, 6.0);
-                                for sel in &answer.selected {
-                                    egui::Frame::new()
-                                        .fill(egui::Color32::from_rgba_unmultiplied(
-                                            self.theme.success_muted.r(),
-                                            self.theme.success_muted.g(),
-                                            self.theme.success_muted.b(),
-                                            item_alpha
-                                        ))
-                                        .corner_radius(6)
-                                        .inner_margin(egui::Margin::symmetric(10, 5))
-                                        .show(ui, |ui| {
-                                            ui.label(egui::RichText::new(sel)
-                                                .color(egui::Color32::from_rgba_unmultiplied(
-                                                    self.theme.success.r(),
-                                                    self.theme.success.g(),
-                                                    self.theme.success.b(),
-                                                    item_alpha
-                                                ))
-                                                .size(12.0));
-                                        });
+        if let Some(diff) = &confirm.diff {
+            egui::Frame::new()
+                .fill(self.theme.surface)
+                .stroke(egui::Stroke::new(1.0, self.theme.border_subtle))
+                .corner_radius(8)
+                .inner_margin(egui::Margin::symmetric(12, 10))
+                .show(ui, |ui| {
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+                        egui::ScrollArea::both()
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                ui.spacing_mut().item_spacing.y = 0.0;
+                                for line in diff.lines() {
+                                    let color = if line.starts_with("+++") || line.starts_with("---") {
+                                        self.theme.text_secondary
+                                    } else if line.starts_with('+') {
+                                        self.theme.success
+                                    } else if line.starts_with('-') {
+                                        self.theme.danger_hover
+                                    } else if line.starts_with("@@") {
+                                        self.theme.accent_hover
+                                    } else {
+                                        self.theme.text_muted
+                                    };
+                                    ui.add(egui::Label::new(egui::RichText::new(line)
+                                        .color(color)
+                                        .monospace()
+                                        .size(self.theme.size_caption)).extend());
                                 }
                             });
-                        });
-                }
+                    });
+                });
+            ui.add_space(10.0);
+        }
+
+        // Type-to-confirm gate
+        if let Some(word) = &confirm.type_to_confirm {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 4.0;
+                ui.label(egui::RichText::new("Type").color(self.theme.text_muted).size(self.theme.size_caption));
+                ui.label(egui::RichText::new(word).color(tone_hover).monospace().size(self.theme.size_caption));
+                ui.label(egui::RichText::new("to confirm").color(self.theme.text_muted).size(self.theme.size_caption));
             });
+            ui.add_space(4.0);
+            let mut text = self.custom.clone();
+            let resp = egui::Frame::new()
+                .fill(self.theme.surface)
+                .stroke(egui::Stroke::new(1.0, if self.custom_focused { tone } else { self.theme.border_subtle }))
+                .corner_radius(8)
+                .inner_margin(egui::Margin::symmetric(12, 8))
+                .show(ui, |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut text)
+                        .desired_width(ui.available_width())
+                        .text_color(self.theme.text_primary)
+                        .horizontal_align(text_align(self.rtl))
+                        .frame(false))
+                }).inner;
+            self.custom_focused = resp.has_focus();
+            if resp.changed() {
+                self.apply(Event::SetCustom { text });
+            }
+        }
 
         ui.add_space(20.0);
 
         // Footer buttons
         ui.horizontal(|ui| {
-            let back_resp = ui.add(
-                egui::Button::new(egui::RichText::new("Edit").color(self.theme.text_muted).size(12.0))
+            let deny_resp = ui.add(
+                egui::Button::new(egui::RichText::new(bidi::visual(confirm.deny_label())).color(self.theme.text_muted).size(self.theme.size_body))
                     .fill(egui::Color32::TRANSPARENT)
                     .stroke(egui::Stroke::NONE)
                     .min_size(egui::vec2(70.0, 38.0))
             );
-            if back_resp.clicked() {
-                self.go_back();
+            if deny_resp.clicked() {
+                self.apply(Event::Deny);
             }
 
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Submit button with custom checkmark icon
+            ui.with_layout(trailing(ui), |ui| {
+                let unlocked = confirm.unlocked(&self.custom);
                 let btn_resp = egui::Frame::new()
-                    .fill(self.theme.success)
+                    .fill(if unlocked { tone } else { self.theme.surface_hover })
                     .corner_radius(8)
                     .inner_margin(egui::Margin::symmetric(16, 10))
                     .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new("Submit").color(self.theme.text_inverse).size(12.0));
-                            ui.add_space(6.0);
-                            // Draw checkmark icon
-                            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-                            let c = rect.center();
-                            let stroke = egui::Stroke::new(1.8, self.theme.text_inverse);
-                            ui.painter().line_segment([c + egui::vec2(-4.0, 0.0), c + egui::vec2(-1.0, 3.0)], stroke);
-                            ui.painter().line_segment([c + egui::vec2(-1.0, 3.0), c + egui::vec2(4.0, -3.0)], stroke);
-                        });
+                        ui.label(egui::RichText::new(bidi::visual(confirm.confirm_label()))
+                            .color(if unlocked { self.theme.text_inverse } else { self.theme.text_muted })
+                            .size(self.theme.size_body));
                     });
 
-                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("submit_btn"), egui::Sense::click());
-                if btn_click.clicked() {
-                    self.submit(ctx);
+                let btn_click = ui.interact(btn_resp.response.rect, ui.id().with("approve_btn"), egui::Sense::click());
+                if unlocked && btn_click.clicked() {
+                    self.apply(Event::Approve);
                 }
             });
         });
@@ -812,12 +3469,117 @@ fn ease_out_cubic(t: f32) -> f32 {
     1.0 - (1.0 - t).powi(3)
 }
 
-impl eframe::App for App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Request continuous repainting for smooth animations
-        ctx.request_repaint();
+/// `ui.vertical` that keeps the surrounding horizontal alignment, so
+/// columns hug the right edge in right-to-left rows.
+fn column<R>(ui: &mut egui::Ui, add: impl FnOnce(&mut egui::Ui) -> R) -> egui::InnerResponse<R> {
+    let align = ui.layout().horizontal_placement();
+    ui.scope_builder(egui::UiBuilder::new().layout(egui::Layout::top_down(align)), add)
+}
+
+/// Layout for the far end of a row: the right in left-to-right rows, the
+/// left in right-to-left ones.
+fn trailing(ui: &egui::Ui) -> egui::Layout {
+    if ui.layout().prefer_right_to_left() {
+        egui::Layout::left_to_right(egui::Align::Center)
+    } else {
+        egui::Layout::right_to_left(egui::Align::Center)
+    }
+}
+
+/// Where typed text starts in a text field.
+fn text_align(rtl: bool) -> egui::Align {
+    if rtl { egui::Align::RIGHT } else { egui::Align::LEFT }
+}
+
+/// What fills the window, a frame at a time; run by either renderer and
+/// offscreen.
+trait Dialog {
+    fn frame(&mut self, ctx: &egui::Context);
+
+    /// Whether something is still in motion, so another frame will differ.
+    fn animating(&self) -> bool;
+}
+
+impl Dialog for App {
+    fn frame(&mut self, ctx: &egui::Context) {
+        self.show(ctx, |_, _| {});
+    }
+
+    fn animating(&self) -> bool {
+        App::animating(self)
+    }
+}
+
+impl App {
+    /// Keyboard shortcuts for the current screen.
+    fn shortcuts(&mut self, ctx: &egui::Context) {
+        if let (Screen::Question(idx), false, false, false) = (&self.screen, self.custom_focused, self.filter_focused, self.note_focused) {
+            let q = &self.questions[*idx];
+            if q.kind.is_choice() {
+                // Number keys pick options in the order they are shown
+                let order = if q.is_long_list() { self.filtered_options(q) } else { (0..q.options.len()).collect() };
+                let pressed = NUMBER_KEYS.iter().position(|&k| ctx.input(|i| i.key_pressed(k)));
+                if let Some(&option) = pressed.and_then(|n| order.get(n)) {
+                    self.pick(option);
+                }
+            } else if q.kind == QuestionKind::Matrix && !q.multi_select {
+                // Number keys answer the first row still unanswered
+                let pressed = NUMBER_KEYS.iter().position(|&k| ctx.input(|i| i.key_pressed(k)));
+                let row = (0..q.rows.len()).find(|&r| !self.row_answered(r));
+                if let (Some(column), Some(row)) = (pressed.filter(|&n| n < q.options.len()), row) {
+                    self.apply(Event::ToggleCell { row, column });
+                }
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) && !self.custom_focused && !self.filter_focused && !self.note_focused {
+            match &self.screen {
+                Screen::Restore => {
+                    if let Some(draft) = self.offered_draft.clone() {
+                        self.apply(Event::Restore { draft });
+                    }
+                }
+                // Destructive actions need a click, or the typed word
+                Screen::Confirm if self.confirm.as_ref().is_some_and(|c| {
+                    c.unlocked(&self.custom) && (!c.danger || c.type_to_confirm.is_some())
+                }) => {
+                    self.apply(Event::Approve);
+                }
+                Screen::Question(_) if self.has_selection() => self.apply(Event::Next),
+                Screen::Review => self.apply(Event::Submit),
+                _ => {}
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            match &self.screen {
+                Screen::Restore => self.apply(Event::Discard),
+                Screen::Confirm => self.apply(Event::Deny),
+                Screen::Question(0) => self.apply(Event::Cancel),
+                _ => self.apply(Event::Back),
+            }
+        }
+    }
+
+    /// Runs one frame, with `header` drawn above the current screen.
+    fn show(&mut self, ctx: &egui::Context, header: impl FnOnce(&mut egui::Ui, &Theme)) {
+        // Feed recorded events back in at their original pace
+        if let Some(replay) = &mut self.replay {
+            let elapsed = replay.start.elapsed().as_millis() as u64;
+            let mut due = Vec::new();
+            while replay.events.front().is_some_and(|(t, _)| *t <= elapsed) {
+                due.extend(replay.events.pop_front().map(|(_, e)| e));
+            }
+            if let Some((t, _)) = replay.events.front() {
+                ctx.request_repaint_after(Duration::from_millis(t - elapsed));
+            }
+            for event in due {
+                self.apply(event);
+            }
+        }
 
-        let mut v = egui::Visuals::dark();
+        let mut v = match self.theme.scheme {
+            ColorScheme::Dark => egui::Visuals::dark(),
+            ColorScheme::Light => egui::Visuals::light(),
+        };
         v.panel_fill = self.theme.bg;
         v.window_fill = self.theme.bg;
         v.widgets.noninteractive.bg_fill = self.theme.surface;
@@ -835,12 +3597,53 @@ impl eframe::App for App {
 
         ctx.set_visuals(v);
 
-        egui::CentralPanel::default()
+        if !self.titled {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(self.requester.window_title()));
+            self.titled = true;
+        }
+
+        // Ctrl/Cmd with +, - and 0 zoom the whole dialog
+        let current = ctx.zoom_factor();
+        let zoom = ctx.input_mut(|i| {
+            use egui::gui_zoom::kb_shortcuts::*;
+            if i.consume_shortcut(&ZOOM_RESET) {
+                Some(self.scale)
+            } else if i.consume_shortcut(&ZOOM_IN) || i.consume_shortcut(&ZOOM_IN_SECONDARY) {
+                Some(current + SCALE_STEP)
+            } else if i.consume_shortcut(&ZOOM_OUT) {
+                Some(current - SCALE_STEP)
+            } else {
+                None
+            }
+        });
+        if let Some(zoom) = zoom {
+            let zoom = (zoom / SCALE_STEP).round() * SCALE_STEP;
+            ctx.set_zoom_factor(zoom.clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end()));
+        }
+
+        // Pull in system fonts for scripts and emoji the loaded ones lack
+        if let Some(mut fonts) = self.fonts.take() {
+            fonts.cover(ctx, || self.shown_text());
+            self.fonts = Some(fonts);
+        }
+
+        let panel = egui::CentralPanel::default()
             .frame(egui::Frame::new()
                 .fill(self.theme.bg)
                 .inner_margin(egui::Margin::symmetric(28, 24)))
             .show(ctx, |ui| {
+                // Right-to-left layouts hang off the right edge; rows follow
+                // the surrounding alignment (see `column` and `trailing`)
+                let align = if self.rtl { egui::Align::Max } else { egui::Align::Min };
+                let content = ui.with_layout(egui::Layout::top_down(align), |ui| {
+                header(ui, &self.theme);
                 match self.screen.clone() {
+                    Screen::Restore => {
+                        self.render_restore(ui, ctx);
+                    }
+                    Screen::Confirm => {
+                        self.render_confirm(ui, ctx);
+                    }
                     Screen::Question(idx) => {
                         if let Some(q) = self.questions.get(idx).cloned() {
                             self.render_question(ui, ctx, q, idx);
@@ -850,68 +3653,551 @@ impl eframe::App for App {
                         self.render_review(ui, ctx);
                     }
                 }
+                });
+                // The panel itself always fills the window; measure what's in it
+                content.response.rect.height()
             });
+        self.fit_window(ctx, panel.inner);
 
-        // Keyboard shortcuts
-        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) && !self.custom_focused {
-            match &self.screen {
-                Screen::Question(_) if self.has_selection() => self.go_next(),
-                Screen::Review => self.submit(ctx),
-                _ => {}
+        // A clicked image, as large as the window allows; keys go to it
+        // rather than the dialog while it's open
+        if let Some(source) = self.zoomed.clone() {
+            let max = ctx.screen_rect().size() - egui::vec2(64.0, 64.0);
+            let modal = egui::Modal::new(egui::Id::new("zoom"))
+                .frame(egui::Frame::new()
+                    .fill(self.theme.surface)
+                    .corner_radius(10)
+                    .inner_margin(egui::Margin::same(8)))
+                .show(ctx, |ui| match self.images.get(ctx, &source) {
+                    images::Image::Ready(texture) => {
+                        let image = egui::Image::new((texture.id(), images::fit(texture, max))).corner_radius(6);
+                        ui.add(image.sense(egui::Sense::click())).clicked()
+                    }
+                    images::Image::Failed(_) => true,
+                });
+            if modal.inner || modal.should_close() {
+                self.zoomed = None;
             }
+        } else {
+            self.shortcuts(ctx);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-            match &self.screen {
-                Screen::Question(0) => self.cancel(ctx),
-                _ => self.go_back(),
+
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.apply(Event::Close);
+        }
+
+        if self.draft_dirty && !self.closing {
+            match DRAFT_INTERVAL.checked_sub(self.draft_saved.elapsed()) {
+                Some(wait) if !wait.is_zero() => ctx.request_repaint_after(wait),
+                _ => self.save_draft(),
             }
         }
+
+        // Input wakes the window up by itself; only keep drawing while
+        // something is moving
+        if self.animating() {
+            ctx.request_repaint();
+        }
+
+        // A queued dialog leaves the window to the queue
+        if self.closing && !self.queued {
+            self.remember_geometry(ctx);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
     }
 }
 
-fn main() -> eframe::Result<()> {
-    let args = Args::parse();
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        Dialog::frame(self, ctx);
+    }
+}
 
-    let content = fs::read_to_string(&args.input).unwrap_or_else(|e| {
+fn load_input(path: &str) -> InputData {
+    let content = fs::read_to_string(path).unwrap_or_else(|e| {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     });
 
-    let input: InputData = serde_json::from_str(&content).unwrap_or_else(|e| {
+    let mut input: InputData = serde_json::from_str(&content).unwrap_or_else(|e| {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     });
 
-    if input.questions.is_empty() {
+    if input.questions.is_empty() && input.confirm.is_none() {
         eprintln!("No questions");
         std::process::exit(1);
     }
+    if let Some(q) = input.questions.iter().find(|q| q.kind == QuestionKind::Matrix && (q.rows.is_empty() || q.options.is_empty())) {
+        eprintln!("Error: matrix question {:?} needs rows and options", q.question);
+        std::process::exit(1);
+    }
+    for q in input.questions.iter().filter(|q| q.kind == QuestionKind::Matrix) {
+        if let Some(key) = q.repeated_row_key() {
+            eprintln!("Error: matrix question {:?} has more than one row keyed {:?}", q.question, key);
+            std::process::exit(1);
+        }
+    }
+    for q in input.questions.iter().filter(|q| q.kind.is_when()) {
+        if let Err(e) = when::validate(q) {
+            eprintln!("Error: question {:?}: {}", q.question, e);
+            std::process::exit(1);
+        }
+    }
+
+    input.normalize();
+    input
+}
+
+/// What was read from stdin, kept to hand on if the process is re-run.
+static STDIN: OnceLock<String> = OnceLock::new();
+
+/// Reads a diff for `confirm --diff`; `-` means stdin.
+fn read_diff(path: &str) -> String {
+    let result = if path == "-" {
+        std::io::read_to_string(std::io::stdin()).map(|s| STDIN.get_or_init(|| s).clone())
+    } else {
+        fs::read_to_string(path)
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    })
+}
+
+fn run_dialog(
+    questions: Vec<Question>,
+    review: ReviewMode,
+    requester: Requester,
+    placement: Placement,
+    appearance: Appearance,
+    recorder: Option<Recorder>,
+    replay: Option<Vec<(u64, Event)>>,
+) -> eframe::Result<Outcome> {
+    // Replays must not pick up or overwrite the user's real drafts
+    let drafts = if replay.is_none() { DraftStore::for_questions(&questions) } else { None };
+    let build = move |tx| App::new(questions, tx).with_review(review).with_requester(requester).with_drafts(drafts);
+    run_window(build, placement, appearance, recorder, replay)
+}
+
+fn run_confirm(
+    confirm: Confirm,
+    requester: Requester,
+    placement: Placement,
+    appearance: Appearance,
+    recorder: Option<Recorder>,
+    replay: Option<Vec<(u64, Event)>>,
+) -> eframe::Result<Outcome> {
+    run_window(move |tx| App::confirm(confirm, tx).with_requester(requester), placement, appearance, recorder, replay)
+}
+
+fn run_window(
+    build: impl FnOnce(mpsc::Sender<Outcome>) -> App + 'static,
+    placement: Placement,
+    appearance: Appearance,
+    recorder: Option<Recorder>,
+    replay: Option<Vec<(u64, Event)>>,
+) -> eframe::Result<Outcome> {
+    let (tx, rx) = mpsc::channel();
+    let viewport = viewport(&placement);
+    let centered = placement.position.is_none();
+    let renderer = appearance.renderer;
+    open_window(viewport, centered, renderer, move |ctx| {
+        let mut app = build(tx)
+            .with_placement(placement)
+            .with_appearance(ctx, &appearance)
+            .with_recorder(recorder);
+        if let Some(events) = replay {
+            app = app.with_replay(events);
+        }
+        app
+    })?;
+    Ok(rx.recv().unwrap_or(Outcome::Cancelled))
+}
+
+/// The window a dialog opens in, before it knows its content.
+fn viewport(placement: &Placement) -> egui::ViewportBuilder {
+    let viewport = egui::ViewportBuilder::default()
+        .with_inner_size(placement.size)
+        .with_min_inner_size(window::MIN_SIZE)
+        .with_title("")
+        .with_decorations(true)
+        .with_transparent(false)
+        .with_always_on_top();
+    match placement.position {
+        Some(position) => viewport.with_position(position),
+        None => viewport,
+    }
+}
+
+/// Opens `viewport` and runs what `create` returns in it until the window
+/// closes, with whichever renderer `renderer` picks.
+fn open_window<D: Dialog + eframe::App + 'static>(
+    viewport: egui::ViewportBuilder,
+    centered: bool,
+    renderer: Renderer,
+    create: impl FnOnce(&egui::Context) -> D + 'static,
+) -> eframe::Result<()> {
+    if renderer == Renderer::Software {
+        return run_software(viewport, centered, create);
+    }
+
+    // Shared so we can tell whether eframe got as far as creating the app
+    let pending = Rc::new(RefCell::new(Some(create)));
+    let opts = eframe::NativeOptions { viewport, centered, ..Default::default() };
+    let result = eframe::run_native("ask-user", opts, Box::new({
+        let pending = pending.clone();
+        move |cc| {
+            let create = pending.borrow_mut().take().expect("app created twice");
+            Ok(Box::new(create(&cc.egui_ctx)))
+        }
+    }));
+    let unused = pending.borrow_mut().take();
+    // An event loop error means there is no display at all, which the
+    // software renderer can't help with
+    match result {
+        Err(e) if renderer == Renderer::Auto
+            && cfg!(feature = "software")
+            && unused.is_some()
+            && !matches!(e, eframe::Error::WinitEventLoop(_)) =>
+        {
+            // Let go of anything `create` holds (such as a socket) first
+            drop(unused);
+            rerun_with_software(e)
+        }
+        result => result,
+    }
+}
+
+#[cfg(feature = "software")]
+fn run_software<D: Dialog>(
+    viewport: egui::ViewportBuilder,
+    centered: bool,
+    create: impl FnOnce(&egui::Context) -> D,
+) -> eframe::Result<()> {
+    software::run(viewport, centered, create)
+}
+
+#[cfg(not(feature = "software"))]
+fn run_software<D: Dialog>(
+    _: egui::ViewportBuilder,
+    _: bool,
+    _: impl FnOnce(&egui::Context) -> D,
+) -> eframe::Result<()> {
+    eprintln!("Error: this build has no software renderer");
+    std::process::exit(1);
+}
+
+/// Set for the software-renderer re-run, whose trace the first run has
+/// already started.
+const RERUN_ENV: &str = "ASKUSERQUESTION_SOFTWARE_RERUN";
+
+/// `args` with any `--renderer` replaced by `--renderer software`.
+fn software_args(args: impl IntoIterator<Item = std::ffi::OsString>) -> Vec<std::ffi::OsString> {
+    let mut out = vec!["--renderer".into(), "software".into()];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--renderer" {
+            args.next();
+        } else if !arg.to_str().is_some_and(|a| a.starts_with("--renderer=")) {
+            out.push(arg);
+        }
+    }
+    out
+}
+
+/// Runs this binary again with `--renderer software` and exits with its
+/// status. OpenGL failed before any window appeared, and winit allows only
+/// one event loop per process, so the fallback needs a fresh one.
+fn rerun_with_software(reason: eframe::Error) -> ! {
+    eprintln!("Warning: OpenGL unavailable ({}); using the software renderer", reason);
+    let exe = std::env::current_exe().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let mut child = std::process::Command::new(exe)
+        .args(software_args(std::env::args_os().skip(1)))
+        .env(RERUN_ENV, "1")
+        .stdin(if STDIN.get().is_some() { std::process::Stdio::piped() } else { std::process::Stdio::inherit() })
+        .spawn()
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), STDIN.get()) {
+        use std::io::Write;
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let status = child.wait().map(|s| s.code().unwrap_or(1)).unwrap_or(1);
+    std::process::exit(status);
+}
+
+/// Answers every question: those with a `recalled` answer are filled in
+/// directly and only the rest are put to the user via `ask`.
+fn collect_answers(
+    questions: &[Question],
+    recalled: Vec<Option<AnswerData>>,
+    ask: impl FnOnce(Vec<Question>) -> eframe::Result<Outcome>,
+) -> eframe::Result<Outcome> {
+    let pending: Vec<Question> = questions.iter().zip(&recalled)
+        .filter(|(_, r)| r.is_none())
+        .map(|(q, _)| q.clone())
+        .collect();
+
+    let (asked, partial) = if pending.is_empty() {
+        (Vec::new(), false)
+    } else {
+        match ask(pending)? {
+            Outcome::Selected(answers) => (answers, false),
+            Outcome::Partial(answers) => (answers, true),
+            other => return Ok(other),
+        }
+    };
+
+    let mut asked = asked.into_iter();
+    let answers = recalled.into_iter().filter_map(|r| r.or_else(|| asked.next())).collect();
+    Ok(if partial { Outcome::Partial(answers) } else { Outcome::Selected(answers) })
+}
 
+fn replay_headless(build: impl FnOnce(mpsc::Sender<Outcome>) -> App, events: &[(u64, Event)]) -> Outcome {
     let (tx, rx) = mpsc::channel();
+    let mut app = build(tx);
+    for (_, event) in events {
+        app.apply(event.clone());
+    }
+    drop(app);
+    rx.recv().unwrap_or(Outcome::Cancelled)
+}
+
+fn replay(path: &str, headless: bool, appearance: impl Fn(&InputData) -> Appearance) -> eframe::Result<()> {
+    let trace = Trace::load(path).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    let mut recalled = trace.remembered.clone();
+    recalled.resize(trace.input.questions.len(), None);
+
+    let result = match &trace.input.confirm {
+        Some(confirm) if headless => replay_headless(|tx| App::confirm(confirm.clone(), tx), &trace.events),
+        Some(confirm) => run_confirm(
+            confirm.clone(),
+            trace.input.requester.clone(),
+            Placement::new(None, None),
+            appearance(&trace.input),
+            None,
+            Some(trace.events.clone()),
+        )?,
+        None => collect_answers(&trace.input.questions, recalled, |questions| {
+            if headless {
+                Ok(replay_headless(|tx| App::new(questions, tx).with_review(trace.input.review), &trace.events))
+            } else {
+                run_dialog(
+                    questions,
+                    trace.input.review,
+                    trace.input.requester.clone(),
+                    Placement::new(None, None),
+                    appearance(&trace.input),
+                    None,
+                    Some(trace.events.clone()),
+                )
+            }
+        })?,
+    };
+
+    let response = Response::from_outcome(result);
+    println!("{}", response.to_json(trace.input.answers_format));
+
+    if trace.response.as_ref().is_some_and(|recorded| *recorded != response.redacted()) {
+        eprintln!("Replay diverged from the recorded response");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn audit_log(args: &Args, config: &Config) -> Option<AuditLog> {
+    let cfg = &config.audit;
+    if !(args.audit || args.audit_log.is_some() || cfg.enabled) {
+        return None;
+    }
+    // Asking without the log the user wanted would go unnoticed
+    if let Some(e) = &config.audit_error {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    let path = args.audit_log.as_ref().map(std::path::PathBuf::from)
+        .or_else(|| cfg.path.clone())
+        .or_else(|| paths::state_dir().map(|d| d.join("audit.jsonl")));
+    match path {
+        Some(path) => Some(AuditLog::new(path, cfg.max_bytes, cfg.keep)),
+        None => {
+            eprintln!("Warning: audit log enabled but no state directory could be determined");
+            None
+        }
+    }
+}
+
+fn forget(keys: &[String], all: bool) {
+    let mut store = Store::load();
+    let project = memory::project();
+    let count = if all {
+        store.clear()
+    } else {
+        keys.iter().filter(|key| store.forget(&project, key)).count()
+    };
+    if let Err(e) = store.save() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    println!("Forgot {} remembered answer{}", count, if count == 1 { "" } else { "s" });
+}
 
-    let opts = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([420.0, 520.0])
-            .with_min_inner_size([360.0, 420.0])
-            .with_title("")
-            .with_decorations(true)
-            .with_transparent(false)
-            .with_always_on_top(),
-        centered: true,
-        ..Default::default()
+fn main() -> eframe::Result<()> {
+    let args = Args::parse();
+
+    if let Some(Command::Forget { keys, all }) = &args.command {
+        forget(keys, *all);
+        return Ok(());
+    }
+
+    let config = Config::load();
+
+    if let Some(Command::Render { input, output, screen, width, height }) = &args.command {
+        let input = load_input(input);
+        let appearance = Appearance::new(&args, &config, &input);
+        if let Err(e) = render::render(&input, *screen, *width, *height, &appearance, output) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Replay { trace, headless }) = &args.command {
+        return replay(trace, *headless, |input| Appearance::new(&args, &config, input));
+    }
+
+    if let Some(Command::Serve) = &args.command {
+        return broker::serve(args.renderer);
+    }
+
+    let input = match &args.command {
+        Some(Command::Confirm { confirm, diff }) => InputData {
+            questions: Vec::new(),
+            confirm: Some(Confirm { diff: diff.as_deref().map(read_diff), ..confirm.clone() }),
+            session_id: None,
+            agent_id: None,
+            answers_format: AnswersFormat::default(),
+            review: ReviewMode::default(),
+            direction: Direction::default(),
+            requester: Requester::default(),
+        },
+        _ => load_input(args.input.as_deref().unwrap_or_default()),
+    };
+
+    // A software re-run adds to the trace the first run started
+    let rerun = std::env::var_os(RERUN_ENV).is_some();
+    let recorder = args.record.as_deref().map(|path| {
+        let recorder = if rerun { Recorder::append(path) } else { Recorder::create(path) };
+        recorder.unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        })
+    });
+    let project = memory::project();
+    let mut store = Store::load();
+    let recalled: Vec<Option<AnswerData>> = input.questions.iter()
+        .map(|q| store.recall(&project, q))
+        .collect();
+
+    if let Some(rec) = recorder.as_ref().filter(|_| !rerun) {
+        rec.input(&input);
+        if recalled.iter().any(Option::is_some) {
+            rec.remembered(&recalled);
+        }
+    }
+
+    // Join the window another caller already has open. Recording needs
+    // the events, which stay in that window's process
+    let queued = |input: InputData| {
+        if args.no_queue || recorder.is_some() {
+            return None;
+        }
+        let appearance = Appearance::new(&args, &config, &input);
+        let cwd = std::env::current_dir().ok();
+        broker::ask(&broker::Request { input, appearance, position: args.position, monitor: args.monitor, cwd })
     };
 
-    let questions = input.questions;
+    // Settled before asking, so a broken audit setting costs no answer
+    let audit = audit_log(&args, &config);
+    let started = Instant::now();
+    let result = match &input.confirm {
+        Some(confirm) => match queued(input.clone()) {
+            Some(outcome) => outcome,
+            None => run_confirm(
+                confirm.clone(),
+                input.requester.clone(),
+                Placement::new(args.position, args.monitor),
+                Appearance::new(&args, &config, &input),
+                recorder.clone(),
+                None,
+            )?,
+        },
+        None => collect_answers(&input.questions, recalled, |questions| {
+            if let Some(outcome) = queued(InputData { questions: questions.clone(), ..input.clone() }) {
+                return Ok(outcome);
+            }
+            run_dialog(
+                questions,
+                input.review,
+                input.requester.clone(),
+                Placement::new(args.position, args.monitor),
+                Appearance::new(&args, &config, &input),
+                recorder.clone(),
+                None,
+            )
+        })?,
+    };
 
-    eframe::run_native("ask-user", opts, Box::new(move |_| {
-        Ok(Box::new(App::new(questions.clone(), tx.clone())))
-    }))?;
+    if let Outcome::Selected(answers) = &result {
+        let mut changed = false;
+        for (q, a) in input.questions.iter().zip(answers) {
+            if let (true, Some(key)) = (a.remember, &q.remember_key) {
+                store.remember(&project, key, a);
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = store.save() {
+                eprintln!("Warning: could not save remembered answers: {}", e);
+            }
+        }
+    }
 
-    let result = rx.recv().ok().flatten();
-    println!("{}", serde_json::to_string(&Response {
-        status: if result.is_some() { "selected" } else { "cancelled" }.into(),
-        answers: result.unwrap_or_default(),
-    }).unwrap());
+    let mut response = Response::from_outcome(result);
+    let redacted = response.redacted();
+    if let Some(rec) = &recorder {
+        rec.response(&redacted);
+    }
+    if let Some(log) = audit {
+        let entry = AuditEntry {
+            ts: audit::utc_now(),
+            session_id: input.session_id.as_deref(),
+            agent_id: input.agent_id.as_deref(),
+            questions: &input.questions,
+            confirm: input.confirm.as_ref(),
+            status: &redacted.status,
+            answers: &redacted.answers,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        if let Err(e) = log.append(&entry) {
+            eprintln!("Warning: could not write audit log: {}", e);
+        }
+    }
+    let mut json = response.to_json(input.answers_format);
+    println!("{}", json);
+    zeroize_json(&mut json);
+    response.answers.iter_mut().for_each(QuestionAnswer::zeroize);
 
     Ok(())
 }
@@ -0,0 +1,207 @@
+use crate::bidi;
+use eframe::egui;
+
+/// Colors and size markdown is drawn in.
+pub struct Style {
+    pub text: egui::Color32,
+    pub strong: egui::Color32,
+    pub link: egui::Color32,
+    pub code_bg: egui::Color32,
+    pub size: f32,
+}
+
+enum Block {
+    Paragraph(String),
+    Heading(String),
+    /// A list item and its marker ("•" or "1.").
+    Item(String, String),
+    Code(String),
+}
+
+/// Splits `text` into the blocks of the small markdown subset shown:
+/// paragraphs, headings, lists and fenced code.
+fn blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph = String::new();
+    let mut lines = text.lines();
+    let flush = |paragraph: &mut String, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(std::mem::take(paragraph)));
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            let code: Vec<&str> = lines.by_ref().take_while(|l| !l.trim_start().starts_with("```")).collect();
+            blocks.push(Block::Code(code.join("\n")));
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(heading.trim_start_matches('#').trim().to_string()));
+        } else if let Some((marker, rest)) = list_item(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Item(marker, rest.to_string()));
+        } else if let (true, true, Some(Block::Item(_, item))) = (paragraph.is_empty(), line.starts_with(' '), blocks.last_mut()) {
+            // An item's continuation line
+            item.push(' ');
+            item.push_str(trimmed);
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(trimmed);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn list_item(line: &str) -> Option<(String, &str)> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("•".to_string(), rest));
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    let rest = line[digits..].strip_prefix(". ")?;
+    (digits > 0).then(|| (format!("{}.", &line[..digits]), rest))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Span {
+    Text { text: String, strong: bool, italic: bool },
+    Code(String),
+    Link { text: String, url: String },
+}
+
+/// Splits a block's text into `**strong**`, `*italic*`, `` `code` `` and
+/// `[links](url)`.
+pub fn spans(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let (mut buf, mut strong, mut italic) = (String::new(), false, false);
+    let mut rest = text;
+    let flush = |buf: &mut String, spans: &mut Vec<Span>, strong, italic| {
+        if !buf.is_empty() {
+            spans.push(Span::Text { text: std::mem::take(buf), strong, italic });
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let prev = text[..text.len() - rest.len()].chars().last();
+        match c {
+            '\\' if after.starts_with(|c: char| c.is_ascii_punctuation()) => {
+                buf.extend(after.chars().next());
+                rest = &after[1..];
+                continue;
+            }
+            '`' if after.contains('`') => {
+                let end = after.find('`').unwrap_or_default();
+                flush(&mut buf, &mut spans, strong, italic);
+                spans.push(Span::Code(after[..end].to_string()));
+                rest = &after[end + 1..];
+                continue;
+            }
+            '*' if after.starts_with('*') => {
+                flush(&mut buf, &mut spans, strong, italic);
+                strong = !strong;
+                rest = &after[1..];
+                continue;
+            }
+            // `_` only at word edges, so snake_case stays as written
+            '*' | '_' if (italic || !after.is_empty() && !after.starts_with(char::is_whitespace))
+                && (c == '*' || if italic {
+                    !after.starts_with(char::is_alphanumeric)
+                } else {
+                    !prev.is_some_and(char::is_alphanumeric)
+                }) => {
+                flush(&mut buf, &mut spans, strong, italic);
+                italic = !italic;
+                rest = after;
+                continue;
+            }
+            '[' => {
+                let link = after.split_once("](").and_then(|(label, tail)| {
+                    let (url, tail) = tail.split_once(')')?;
+                    (!label.contains(']')).then_some((label, url, tail))
+                });
+                if let Some((label, url, tail)) = link {
+                    flush(&mut buf, &mut spans, strong, italic);
+                    spans.push(Span::Link { text: label.to_string(), url: url.to_string() });
+                    rest = tail;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        buf.push(c);
+        rest = after;
+    }
+    flush(&mut buf, &mut spans, strong, italic);
+    spans
+}
+
+/// Draws `text` as markdown, wrapping to the available width.
+pub fn show(ui: &mut egui::Ui, text: &str, style: &Style) {
+    for (i, block) in blocks(text).iter().enumerate() {
+        if i > 0 {
+            ui.add_space(4.0);
+        }
+        match block {
+            Block::Paragraph(text) => inline(ui, text, style, false),
+            Block::Heading(text) => inline(ui, text, style, true),
+            Block::Item(marker, text) => {
+                ui.horizontal_top(|ui| {
+                    ui.label(egui::RichText::new(marker).color(style.text).size(style.size));
+                    ui.vertical(|ui| inline(ui, text, style, false));
+                });
+            }
+            Block::Code(code) => {
+                egui::Frame::new()
+                    .fill(style.code_bg)
+                    .corner_radius(6)
+                    .inner_margin(egui::Margin::symmetric(10, 8))
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(code).monospace().color(style.text).size(style.size - 1.0));
+                    });
+            }
+        }
+    }
+}
+
+fn inline(ui: &mut egui::Ui, text: &str, style: &Style, heading: bool) {
+    let spans = spans(text);
+    // Right-to-left text is reordered as a whole, without its formatting
+    let plain: String = spans.iter().map(|s| match s {
+        Span::Text { text, .. } | Span::Code(text) | Span::Link { text, .. } => text.as_str(),
+    }).collect();
+    if plain.chars().any(bidi::is_rtl) {
+        bidi::label(ui, egui::RichText::new(plain).color(if heading { style.strong } else { style.text }).size(style.size));
+        return;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            match span {
+                Span::Text { text, strong, italic } => {
+                    let mut rich = egui::RichText::new(text).size(style.size)
+                        .color(if strong || heading { style.strong } else { style.text });
+                    if italic {
+                        rich = rich.italics();
+                    }
+                    ui.label(rich);
+                }
+                Span::Code(code) => {
+                    ui.label(egui::RichText::new(code).monospace().size(style.size - 1.0)
+                        .color(style.strong)
+                        .background_color(style.code_bg));
+                }
+                Span::Link { text, url } => {
+                    ui.hyperlink_to(egui::RichText::new(text).size(style.size).color(style.link), url);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,151 @@
+use crate::{pathpick, paths, when, AnswerData, Question, QuestionKind};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Answers the user asked us to remember, scoped per project directory and
+/// keyed by the question's `rememberKey`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Store {
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry {
+    project: String,
+    key: String,
+    multi: bool,
+    selected: Vec<String>,
+    indices: Vec<i32>,
+    #[serde(default)]
+    values: Vec<String>,
+    #[serde(default)]
+    other: Option<String>,
+}
+
+/// The project a remembered answer belongs to: the directory the caller
+/// launched us from.
+pub fn project() -> String {
+    std::env::current_dir()
+        .and_then(|d| d.canonicalize())
+        .map(|d| d.display().to_string())
+        .unwrap_or_default()
+}
+
+impl Store {
+    fn path() -> Option<PathBuf> {
+        paths::state_dir().map(|d| d.join("remembered.json"))
+    }
+
+    pub fn load() -> Self {
+        let Some(content) = Self::path().and_then(|p| fs::read_to_string(p).ok()) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring unreadable remembered answers: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no state directory")
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Looks up a remembered answer for `q`. Only answers whose options still
+    /// exist (matched by value, or by label for options without one) in the
+    /// same selection mode are reused; labels and indices are refreshed from
+    /// the current options. Remembered paths, dates, times and durations
+    /// must still pass the question's checks. Matrix and secret questions are
+    /// always asked.
+    pub fn recall(&self, project: &str, q: &Question) -> Option<AnswerData> {
+        let key = q.remember_key.as_deref()?;
+        let entry = self.entries.iter().find(|e| e.project == project && e.key == key)?;
+        if matches!(q.kind, QuestionKind::Matrix | QuestionKind::Secret) {
+            return None;
+        }
+        if q.kind == QuestionKind::Path {
+            let picked = pathpick::check(q, entry.selected.first()?).ok()?;
+            let mut answer = AnswerData::for_path(q, &picked);
+            answer.remembered = true;
+            return Some(answer);
+        }
+        if q.kind.is_when() {
+            let value = when::check(q, entry.selected.first()?).ok()?;
+            let mut answer = AnswerData::for_when(q, &value);
+            answer.remembered = true;
+            return Some(answer);
+        }
+        if entry.multi != q.multi_select {
+            return None;
+        }
+
+        let mut selected = Vec::new();
+        let mut indices = Vec::new();
+        let mut other = entry.other.clone().filter(|_| q.allow_other);
+        for (n, (label, &idx)) in entry.selected.iter().zip(&entry.indices).enumerate() {
+            // Entries saved before "Other" had its own field used index -1
+            if idx < 0 {
+                other = Some(label.clone()).filter(|_| q.allow_other);
+                continue;
+            }
+            let value = entry.values.get(n);
+            let pos = q.options.iter().position(|o| value.is_some() && o.value.as_ref() == value)
+                .or_else(|| q.options.iter().position(|o| &o.label == label))?;
+            selected.push(q.options[pos].label.clone());
+            indices.push(pos as i32);
+        }
+        if selected.is_empty() && other.is_none() {
+            return None;
+        }
+
+        Some(AnswerData {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            header: q.header.clone(),
+            values: q.values_for(&selected, &indices),
+            selected,
+            indices,
+            other,
+            multi: q.multi_select,
+            remember: false,
+            remembered: true,
+            accepted_default: None,
+            path: None,
+            relative_path: None,
+            note: None,
+            rows: None,
+            secret: false,
+        })
+    }
+
+    pub fn remember(&mut self, project: &str, key: &str, answer: &AnswerData) {
+        self.forget(project, key);
+        self.entries.push(Entry {
+            project: project.to_string(),
+            key: key.to_string(),
+            multi: answer.multi,
+            selected: answer.selected.clone(),
+            indices: answer.indices.clone(),
+            values: answer.values.clone().unwrap_or_default(),
+            other: answer.other.clone(),
+        });
+    }
+
+    pub fn forget(&mut self, project: &str, key: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| !(e.project == project && e.key == key));
+        self.entries.len() != before
+    }
+
+    pub fn clear(&mut self) -> usize {
+        std::mem::take(&mut self.entries).len()
+    }
+}
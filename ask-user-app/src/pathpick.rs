@@ -0,0 +1,211 @@
+use crate::{PathMode, Question};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// A validated answer to a path question.
+#[derive(Clone)]
+pub struct Picked {
+    pub absolute: PathBuf,
+    /// Relative to the question's base directory.
+    pub relative: PathBuf,
+}
+
+/// One row in the in-dialog browser.
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Resolved once when the folder is listed, to compare with the pick.
+    pub canonical: PathBuf,
+}
+
+/// How long a check is trusted before the filesystem is looked at again.
+const RECHECK: Duration = Duration::from_secs(1);
+
+/// `check`'s result for the text last checked, so the several checks in a
+/// frame touch the filesystem once.
+#[derive(Default)]
+pub struct Checked(RefCell<Option<LastCheck>>);
+
+struct LastCheck {
+    text: String,
+    at: Instant,
+    result: Result<Picked, String>,
+}
+
+impl Checked {
+    pub fn check(&self, q: &Question, text: &str) -> Result<Picked, String> {
+        let mut last = self.0.borrow_mut();
+        match &*last {
+            Some(last) if last.text == text && last.at.elapsed() < RECHECK => last.result.clone(),
+            _ => {
+                let result = check(q, text);
+                *last = Some(LastCheck { text: text.to_string(), at: Instant::now(), result: result.clone() });
+                result
+            }
+        }
+    }
+
+    /// Forgets the last result, for a new question or before accepting an
+    /// answer.
+    pub fn clear(&self) {
+        self.0.borrow_mut().take();
+    }
+}
+
+fn expand_home(text: &str) -> PathBuf {
+    match (text.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => {
+            PathBuf::from(home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(text),
+    }
+}
+
+/// Lexically resolves `.` and `..` so non-existent paths (save mode) can be
+/// made absolute too.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => { out.pop(); }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn base_dir(q: &Question) -> PathBuf {
+    let cwd = q.cwd.clone().or_else(|| std::env::current_dir().ok()).unwrap_or_default();
+    let base = q.base_dir.as_deref().map(|b| cwd.join(expand_home(b))).unwrap_or(cwd);
+    normalize(&base)
+}
+
+pub fn resolve(q: &Question, text: &str) -> PathBuf {
+    normalize(&base_dir(q).join(expand_home(text.trim())))
+}
+
+/// How a picked path is written into the text field: relative to the base
+/// directory when inside it, absolute otherwise.
+pub fn display(q: &Question, path: &Path) -> String {
+    match path.strip_prefix(base_dir(q)) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".into(),
+        Ok(rel) => rel.display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// `path` relative to `base`, walking up with `..` where needed.
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut out: PathBuf = base[common..].iter().map(|_| Component::ParentDir).collect();
+    out.extend(&path[common..]);
+    if out.as_os_str().is_empty() { PathBuf::from(".") } else { out }
+}
+
+/// Checks `text` against the question's mode, filter and existence rules.
+pub fn check(q: &Question, text: &str) -> Result<Picked, String> {
+    if text.trim().is_empty() {
+        return Err(String::new());
+    }
+    let path = resolve(q, text);
+    let must_exist = q.must_exist.unwrap_or(q.mode != PathMode::Save);
+
+    match q.mode {
+        PathMode::File if path.is_dir() => return Err("That's a folder; pick a file".into()),
+        PathMode::Directory if path.is_file() => return Err("That's a file; pick a folder".into()),
+        PathMode::Save if path.is_dir() => return Err("That's a folder; enter a file name".into()),
+        PathMode::Save if !path.parent().is_some_and(Path::is_dir) => {
+            return Err("The containing folder doesn't exist".into());
+        }
+        _ => {}
+    }
+    if must_exist && !path.exists() {
+        return Err("Nothing exists at that path".into());
+    }
+    if q.mode != PathMode::Directory {
+        if let Some(filter) = &q.filter {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            if !glob_match(filter, &name) {
+                return Err(format!("Must match {}", filter));
+            }
+        }
+    }
+
+    let absolute = if path.exists() { path.canonicalize().unwrap_or(path) } else { path };
+    let base = base_dir(q);
+    let base = base.canonicalize().unwrap_or(base);
+    Ok(Picked { relative: relative_to(&absolute, &base), absolute })
+}
+
+/// Visible entries of `dir` for the browser: folders first, then files
+/// matching the question's filter (none in directory mode).
+pub fn list_dir(q: &Question, dir: &Path) -> Vec<Entry> {
+    let Ok(read) = fs::read_dir(dir) else { return Vec::new() };
+    let mut entries: Vec<Entry> = read.flatten().filter_map(|e| {
+        let name = e.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            return None;
+        }
+        let path = e.path();
+        let is_dir = path.is_dir();
+        let shown = is_dir || (q.mode != PathMode::Directory
+            && q.filter.as_deref().is_none_or(|f| glob_match(f, &name)));
+        shown.then(|| Entry { name, is_dir, canonical: path.canonicalize().unwrap_or(path) })
+    }).collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    entries
+}
+
+/// Matches a file name against a glob with `*`, `?` and `{a,b}`
+/// alternatives. Several globs can be separated by `;`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    pattern.split(';').map(str::trim).filter(|p| !p.is_empty()).any(|p| {
+        expand_braces(p).iter().any(|p| wildcard(&p.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>()))
+    })
+}
+
+pub fn expand_braces(pattern: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (pattern.find('{'), pattern.find('}')) else {
+        return vec![pattern.to_string()];
+    };
+    if close < open {
+        return vec![pattern.to_string()];
+    }
+    let (head, tail) = (&pattern[..open], &pattern[close + 1..]);
+    pattern[open + 1..close].split(',')
+        .flat_map(|alt| expand_braces(&format!("{}{}{}", head, alt, tail)))
+        .collect()
+}
+
+/// Iterative match: on a mismatch, only the latest `*` takes one more
+/// character, so patterns like `*a*a*b` don't backtrack exponentially.
+fn wildcard(p: &[char], s: &[char]) -> bool {
+    let (mut pi, mut si) = (0, 0);
+    let mut star = None;
+    while si < s.len() {
+        match p.get(pi) {
+            Some('*') => {
+                star = Some((pi, si));
+                pi += 1;
+            }
+            Some(c) if *c == '?' || c.eq_ignore_ascii_case(&s[si]) => {
+                pi += 1;
+                si += 1;
+            }
+            _ => match star {
+                Some((sp, ss)) => {
+                    star = Some((sp, ss + 1));
+                    pi = sp + 1;
+                    si = ss + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
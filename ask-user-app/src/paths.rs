@@ -0,0 +1,42 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "askuserquestion";
+
+pub fn home() -> Option<PathBuf> {
+    env::var_os("HOME").filter(|h| !h.is_empty()).map(PathBuf::from)
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// Where persistent per-user state (logs, history) lives.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        home().map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_STATE_HOME").or_else(|| home().map(|h| h.join(".local").join("state")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// Where the user's `config.json` lives.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        home().map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| home().map(|h| h.join(".config")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// Where sockets for talking to a running window live: private to the
+/// user and cleared on logout where the OS provides such a place.
+pub fn runtime_dir() -> Option<PathBuf> {
+    env_dir("XDG_RUNTIME_DIR").map(|d| d.join(APP_DIR)).or_else(state_dir)
+}
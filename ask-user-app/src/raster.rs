@@ -0,0 +1,174 @@
+use eframe::egui;
+use egui::epaint::{ClippedPrimitive, ImageData, Primitive, Vertex};
+use std::collections::HashMap;
+
+/// Pixels being drawn, as `0x00RRGGBB`.
+struct Target<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+    height: usize,
+}
+
+impl Target<'_> {
+    fn clear(&mut self, color: egui::Color32) {
+        let [r, g, b, _] = color.to_array();
+        self.pixels.fill(u32::from_be_bytes([0, r, g, b]));
+    }
+
+    /// Draws premultiplied `src` over the pixel at `i`.
+    fn blend(&mut self, i: usize, src: [f32; 4]) {
+        let [_, r, g, b] = self.pixels[i].to_be_bytes();
+        let keep = 1.0 - src[3] / 255.0;
+        let mix = |s: f32, d: u8| (s + d as f32 * keep).round().clamp(0.0, 255.0) as u8;
+        self.pixels[i] = u32::from_be_bytes([0, mix(src[0], r), mix(src[1], g), mix(src[2], b)]);
+    }
+}
+
+/// A texture egui uploaded, in premultiplied sRGBA.
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<egui::Color32>,
+    linear: bool,
+}
+
+impl Texture {
+    fn texel(&self, x: usize, y: usize) -> [f32; 4] {
+        let [r, g, b, a] = self.pixels[y * self.size[0] + x].to_array();
+        [r as f32, g as f32, b as f32, a as f32]
+    }
+
+    fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        let [w, h] = self.size;
+        if !self.linear {
+            let x = ((u * w as f32) as usize).min(w - 1);
+            let y = ((v * h as f32) as usize).min(h - 1);
+            return self.texel(x, y);
+        }
+        let x = (u * w as f32 - 0.5).clamp(0.0, (w - 1) as f32);
+        let y = (v * h as f32 - 0.5).clamp(0.0, (h - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+        let (fx, fy) = (x.fract(), y.fract());
+        let lerp = |a: [f32; 4], b: [f32; 4], t: f32| std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t);
+        let top = lerp(self.texel(x0, y0), self.texel(x1, y0), fx);
+        let bottom = lerp(self.texel(x0, y1), self.texel(x1, y1), fx);
+        lerp(top, bottom, fy)
+    }
+}
+
+/// Paints egui's output on the CPU, keeping the textures it uploads.
+#[derive(Default)]
+pub struct Raster {
+    textures: HashMap<egui::TextureId, Texture>,
+}
+
+impl Raster {
+    /// Applies a frame's texture changes without drawing it.
+    pub fn upload(&mut self, textures: &egui::TexturesDelta) {
+        self.update(&textures.set);
+        for id in &textures.free {
+            self.textures.remove(id);
+        }
+    }
+
+    /// Draws tessellated `primitives` into `pixels`, rows of `width` pixels
+    /// as `0x00RRGGBB`, over a `background` fill.
+    pub fn draw(&self, primitives: &[ClippedPrimitive], ppp: f32, pixels: &mut [u32], width: usize, background: egui::Color32) {
+        let height = pixels.len().checked_div(width).unwrap_or(0);
+        let mut target = Target { pixels, width, height };
+        target.clear(background);
+        for primitive in primitives {
+            self.paint(&mut target, primitive, ppp);
+        }
+    }
+
+    fn update(&mut self, set: &[(egui::TextureId, egui::epaint::ImageDelta)]) {
+        for (id, delta) in set {
+            let (size, pixels): ([usize; 2], Vec<egui::Color32>) = match &delta.image {
+                ImageData::Color(image) => (image.size, image.pixels.clone()),
+                ImageData::Font(image) => (image.size, image.srgba_pixels(None).collect()),
+            };
+            let linear = delta.options.magnification == egui::TextureFilter::Linear;
+            match (delta.pos, self.textures.get_mut(id)) {
+                (Some([x, y]), Some(texture)) => {
+                    for row in 0..size[1] {
+                        let start = (y + row) * texture.size[0] + x;
+                        texture.pixels[start..start + size[0]]
+                            .copy_from_slice(&pixels[row * size[0]..(row + 1) * size[0]]);
+                    }
+                }
+                _ => {
+                    self.textures.insert(*id, Texture { size, pixels, linear });
+                }
+            }
+        }
+    }
+
+    fn paint(&self, target: &mut Target, primitive: &ClippedPrimitive, ppp: f32) {
+        let Primitive::Mesh(mesh) = &primitive.primitive else { return };
+        let Some(texture) = self.textures.get(&mesh.texture_id) else { return };
+        let clip = primitive.clip_rect;
+        let clip = [
+            (clip.min.x * ppp).round().max(0.0) as usize,
+            (clip.min.y * ppp).round().max(0.0) as usize,
+            ((clip.max.x * ppp).round().max(0.0) as usize).min(target.width),
+            ((clip.max.y * ppp).round().max(0.0) as usize).min(target.height),
+        ];
+        for tri in mesh.indices.chunks_exact(3) {
+            let v = [tri[0], tri[1], tri[2]].map(|i| &mesh.vertices[i as usize]);
+            triangle(target, texture, clip, v, ppp);
+        }
+    }
+}
+
+fn edge(a: egui::Pos2, b: egui::Pos2, p: egui::Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether a pixel centre exactly on edge `a`-`b` is drawn, so that of two
+/// triangles sharing the edge only one draws it.
+fn owns_edge(a: egui::Pos2, b: egui::Pos2) -> bool {
+    b.y > a.y || (b.y == a.y && b.x < a.x)
+}
+
+fn triangle(target: &mut Target, texture: &Texture, clip: [usize; 4], mut v: [&Vertex; 3], ppp: f32) {
+    let mut p = v.map(|v| v.pos * ppp);
+    let mut area = edge(p[0], p[1], p[2]);
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+    if area < 0.0 {
+        v.swap(1, 2);
+        p.swap(1, 2);
+        area = -area;
+    }
+
+    let x0 = p.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).floor().max(clip[0] as f32) as usize;
+    let y0 = p.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor().max(clip[1] as f32) as usize;
+    let x1 = (p.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(clip[2]);
+    let y1 = (p.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(clip[3]);
+    let edges = [(p[1], p[2]), (p[2], p[0]), (p[0], p[1])];
+    let owned = edges.map(|(a, b)| owns_edge(a, b));
+    let colors = v.map(|v| v.color.to_array().map(|c| c as f32));
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let c = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+            let e = edges.map(|(a, b)| edge(a, b, c));
+            if (0..3).any(|i| e[i] < 0.0 || (e[i] == 0.0 && !owned[i])) {
+                continue;
+            }
+            let w = e.map(|e| e / area);
+            let u = w[0] * v[0].uv.x + w[1] * v[1].uv.x + w[2] * v[2].uv.x;
+            let t = w[0] * v[0].uv.y + w[1] * v[1].uv.y + w[2] * v[2].uv.y;
+            let texel = texture.sample(u, t);
+            let src: [f32; 4] = std::array::from_fn(|i| {
+                let color = w[0] * colors[0][i] + w[1] * colors[1][i] + w[2] * colors[2][i];
+                color * texel[i] / 255.0
+            });
+            if src[3] > 0.0 || src[..3].iter().any(|&c| c > 0.0) {
+                target.blend(y * target.width + x, src);
+            }
+        }
+    }
+}
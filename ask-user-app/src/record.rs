@@ -0,0 +1,111 @@
+use crate::{AnswerData, Event, InputData, Response};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// One line of a JSONL session trace.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEntry {
+    Input { t_ms: u64, input: Box<InputData> },
+    /// Answers filled in from the remembered-answer store, one slot per
+    /// question (`null` for questions that were asked).
+    Remembered { t_ms: u64, answers: Vec<Option<AnswerData>> },
+    Event { t_ms: u64, event: Event },
+    Response { t_ms: u64, response: Response },
+}
+
+/// Appends trace entries to a file. Cheap to clone so the dialog and `main`
+/// can share the same trace.
+#[derive(Clone)]
+pub struct Recorder {
+    file: Arc<Mutex<fs::File>>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        Ok(Self {
+            file: Arc::new(Mutex::new(fs::File::create(path)?)),
+            start: Instant::now(),
+        })
+    }
+
+    /// Carries on a trace an earlier process started.
+    pub fn append(path: &str) -> std::io::Result<Self> {
+        Ok(Self {
+            file: Arc::new(Mutex::new(fs::OpenOptions::new().create(true).append(true).open(path)?)),
+            start: Instant::now(),
+        })
+    }
+
+    fn t_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn write(&self, entry: &TraceEntry) {
+        // Each entry is flushed on its own so a crashed session still leaves
+        // a usable trace behind.
+        if let (Ok(mut file), Ok(line)) = (self.file.lock(), serde_json::to_string(entry)) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    pub fn input(&self, input: &InputData) {
+        self.write(&TraceEntry::Input { t_ms: self.t_ms(), input: Box::new(input.clone()) });
+    }
+
+    pub fn remembered(&self, answers: &[Option<AnswerData>]) {
+        self.write(&TraceEntry::Remembered { t_ms: self.t_ms(), answers: answers.to_vec() });
+    }
+
+    pub fn event(&self, event: &Event) {
+        self.write(&TraceEntry::Event { t_ms: self.t_ms(), event: event.clone() });
+    }
+
+    pub fn response(&self, response: &Response) {
+        self.write(&TraceEntry::Response { t_ms: self.t_ms(), response: response.clone() });
+    }
+}
+
+/// A trace loaded back from disk.
+pub struct Trace {
+    pub input: InputData,
+    pub remembered: Vec<Option<AnswerData>>,
+    pub events: Vec<(u64, Event)>,
+    pub response: Option<Response>,
+}
+
+impl Trace {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        let mut input = None;
+        let mut remembered = Vec::new();
+        let mut events = Vec::new();
+        let mut response = None;
+
+        for (n, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: TraceEntry = serde_json::from_str(&line)
+                .map_err(|e| format!("line {}: {}", n + 1, e))?;
+            match entry {
+                TraceEntry::Input { input: i, .. } => input = Some(*i),
+                TraceEntry::Remembered { answers, .. } => remembered = answers,
+                TraceEntry::Event { t_ms, event } => events.push((t_ms, event)),
+                TraceEntry::Response { response: r, .. } => response = Some(r),
+            }
+        }
+
+        Ok(Self {
+            input: input.ok_or("trace has no input entry")?,
+            remembered,
+            events,
+            response,
+        })
+    }
+}
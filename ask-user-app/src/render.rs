@@ -0,0 +1,224 @@
+use crate::raster::Raster;
+use crate::window::{self, Placement};
+use crate::{pathpick, when, App, Appearance, Dialog, Event, InputData, Question, QuestionKind, ReviewMode};
+use eframe::egui;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc;
+
+/// Frames run at most while the layout settles (fonts load, the height
+/// fits the content).
+const MAX_FRAMES: usize = 10;
+
+/// Which screen `render` draws (`--screen`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shown {
+    /// 1-based, like the step counter in the dialog.
+    Question(usize),
+    Review,
+}
+
+impl FromStr for Shown {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "review" => Ok(Self::Review),
+            _ => s.parse().ok().filter(|&n| n > 0).map(Self::Question)
+                .ok_or_else(|| "expected review or a question number starting at 1".to_string()),
+        }
+    }
+}
+
+/// Lays out `input` offscreen at `shown`, `width` points wide and either
+/// `height` tall or as tall as its content, and writes it to `path` as a
+/// PNG.
+pub fn render(
+    input: &InputData,
+    shown: Shown,
+    width: f32,
+    height: Option<f32>,
+    appearance: &Appearance,
+    path: &Path,
+) -> Result<(), String> {
+    let (tx, _rx) = mpsc::channel();
+    let size = egui::vec2(width, height.unwrap_or(window::DEFAULT_SIZE.y));
+    let app = match (&input.confirm, shown) {
+        (Some(confirm), Shown::Question(1)) => App::confirm(confirm.clone(), tx).with_requester(input.requester.clone()),
+        (Some(_), _) => return Err("a confirmation only has one screen".to_string()),
+        (None, Shown::Question(n)) if n > input.questions.len() => {
+            return Err(format!("question {} asked for, but there are only {}", n, input.questions.len()));
+        }
+        (None, _) => App::new(input.questions.clone(), tx)
+            .with_review(ReviewMode::Always)
+            .with_requester(input.requester.clone()),
+    };
+    let mut offscreen = Offscreen::new(size);
+    let mut app = app
+        .with_placement(Placement::offscreen(size, height.is_some()))
+        .with_appearance(&offscreen.ctx, appearance);
+    // Draw where animations end up, not their first frame
+    app.reduced_motion = true;
+
+    match shown {
+        _ if input.confirm.is_some() => {}
+        Shown::Question(n) => app.show_question(n - 1),
+        Shown::Review => {
+            // Something to review: defaults where set, else the first option
+            for (i, q) in input.questions.iter().enumerate() {
+                if !app.has_selection() && q.kind.is_choice() && !q.options.is_empty() {
+                    app.apply(Event::Toggle { option: 0 });
+                }
+                if q.kind.is_when() && !app.has_selection() {
+                    app.apply(Event::SetCustom { text: when::text(&when::start(q)) });
+                }
+                if q.kind == QuestionKind::Path && !app.has_selection() {
+                    if let Some(path) = sample_path(q) {
+                        app.apply(Event::SetCustom { text: path });
+                    }
+                }
+                if q.kind == QuestionKind::Secret && !app.has_selection() {
+                    app.apply(Event::SetCustom { text: "correct horse battery staple".into() });
+                }
+                if q.kind == QuestionKind::Matrix {
+                    for row in 0..q.rows.len() {
+                        if !app.row_answered(row) {
+                            app.apply(Event::ToggleCell { row, column: 0 });
+                        }
+                    }
+                }
+                if app.current_answer().is_none() {
+                    return Err(format!("no answer to fill in for question {} on the review screen", i + 1));
+                }
+                app.apply(Event::Next);
+            }
+        }
+    }
+
+    offscreen.settle(&mut app);
+    offscreen.write_png(path)
+}
+
+/// Runs an `App` without a window, drawing each frame on the CPU into an
+/// image the size the window would be.
+pub struct Offscreen {
+    pub ctx: egui::Context,
+    raster: Raster,
+    /// Window size in points; follows the app's resize requests.
+    size: egui::Vec2,
+    /// The last frame, drawn only once its pixels are asked for.
+    shapes: Vec<egui::epaint::ClippedShape>,
+    ppp: f32,
+    drawn: bool,
+    pixels: Vec<u32>,
+    image: [usize; 2],
+}
+
+impl Offscreen {
+    pub fn new(size: egui::Vec2) -> Self {
+        Self {
+            ctx: egui::Context::default(),
+            raster: Raster::default(),
+            size,
+            shapes: Vec::new(),
+            ppp: 1.0,
+            drawn: false,
+            pixels: Vec::new(),
+            image: [0, 0],
+        }
+    }
+
+    /// Runs one frame with `events` as its input. Returns whether the app
+    /// asked for a new size.
+    pub fn frame(&mut self, app: &mut impl Dialog, events: Vec<egui::Event>) -> bool {
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, self.size);
+        let mut raw = egui::RawInput { screen_rect: Some(rect), events, ..Default::default() };
+        raw.viewports.insert(egui::ViewportId::ROOT, egui::ViewportInfo {
+            native_pixels_per_point: Some(1.0),
+            inner_rect: Some(rect),
+            ..Default::default()
+        });
+
+        let output = self.ctx.run(raw, |ctx| app.frame(ctx));
+        self.raster.upload(&output.textures_delta);
+        self.ppp = output.pixels_per_point;
+        self.image = [(self.size.x * self.ppp).round() as usize, (self.size.y * self.ppp).round() as usize];
+        self.shapes = output.shapes;
+        self.drawn = false;
+
+        // The dialog asks for a new size the way it would resize its window
+        let resize = output.viewport_output.get(&egui::ViewportId::ROOT)
+            .and_then(|v| v.commands.iter().rev().find_map(|c| match c {
+                egui::ViewportCommand::InnerSize(s) => Some(*s),
+                _ => None,
+            }));
+        if let Some(size) = resize {
+            self.size = size;
+        }
+        resize.is_some()
+    }
+
+    /// What the last frame drew, before tessellation.
+    #[cfg(test)]
+    pub fn shapes(&self) -> &[egui::epaint::ClippedShape] {
+        &self.shapes
+    }
+
+    /// Runs frames until the layout stops changing (fonts load, the height
+    /// fits the content, animations end).
+    pub fn settle(&mut self, app: &mut impl Dialog) {
+        for frame in 0..MAX_FRAMES {
+            let resized = self.frame(app, Vec::new());
+            if !resized && frame > 0 && !app.animating() {
+                break;
+            }
+        }
+    }
+
+    fn draw(&mut self) {
+        if self.drawn {
+            return;
+        }
+        self.pixels.resize(self.image[0] * self.image[1], 0);
+        let background = self.ctx.style().visuals.panel_fill;
+        let primitives = self.ctx.tessellate(self.shapes.clone(), self.ppp);
+        self.raster.draw(&primitives, self.ppp, &mut self.pixels, self.image[0], background);
+        self.drawn = true;
+    }
+
+    /// The last frame, as rows of `0x00RRGGBB`, and its size.
+    #[cfg(test)]
+    pub fn image(&mut self) -> (&[u32], [usize; 2]) {
+        self.draw();
+        (&self.pixels, self.image)
+    }
+
+    pub fn write_png(&mut self, path: &Path) -> Result<(), String> {
+        self.draw();
+        write_png(path, &self.pixels, self.image).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// A path `q` accepts: its base folder, a file in it, or a name made
+/// from its filter.
+fn sample_path(q: &Question) -> Option<String> {
+    let listed = pathpick::list_dir(q, &pathpick::base_dir(q)).into_iter().map(|e| e.name);
+    let glob = q.filter.as_deref().and_then(|f| f.split(';').next()).unwrap_or("*.txt");
+    let named = pathpick::expand_braces(glob.trim()).into_iter().map(|g| g.replace('*', "untitled").replace('?', "x"));
+    std::iter::once(".".to_string()).chain(listed).chain(named).find(|p| pathpick::check(q, p).is_ok())
+}
+
+fn write_png(path: &Path, pixels: &[u32], [width, height]: [usize; 2]) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = pixels.iter()
+        .flat_map(|p| {
+            let [_, r, g, b] = p.to_be_bytes();
+            [r, g, b]
+        })
+        .collect();
+    encoder.write_header()?.write_image_data(&data)
+}
@@ -0,0 +1,88 @@
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Who is asking and why, shown in a banner above the dialog and in the
+/// window title.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Requester {
+    /// Short name for the task the questions belong to, e.g. "Set up CI".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Source::is_empty")]
+    pub source: Source,
+    /// Why the agent is asking, in markdown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// An emoji or a couple of letters shown beside the title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// Where a request comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Source {
+    /// Name of the agent asking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Path of the project it works in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+/// Characters of a session id worth showing.
+const SESSION_CHARS: usize = 8;
+
+impl Source {
+    fn is_empty(&self) -> bool {
+        self.agent.is_none() && self.project.is_none() && self.session.is_none()
+    }
+
+    /// The project's folder name.
+    pub fn project_name(&self) -> Option<&str> {
+        let project = self.project.as_deref()?;
+        Path::new(project).file_name().and_then(|n| n.to_str()).or(Some(project))
+    }
+
+    /// Agent, project and session on one line, e.g.
+    /// "build · ~/src/app · 3f9c2a1b".
+    pub fn line(&self) -> Option<String> {
+        let project = self.project.as_deref().map(|p| {
+            let home = paths::home();
+            match home.as_deref().and_then(|h| Path::new(p).strip_prefix(h).ok()) {
+                Some(rest) => Path::new("~").join(rest).display().to_string(),
+                None => p.to_string(),
+            }
+        });
+        let session = self.session.as_deref().map(|s| s.chars().take(SESSION_CHARS).collect());
+        let parts: Vec<String> = [self.agent.clone(), project, session].into_iter().flatten().collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+impl Requester {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.source.is_empty() && self.context.is_none() && self.icon.is_none()
+    }
+
+    /// "Set up CI — build · app", or as much of it as is known.
+    pub fn window_title(&self) -> String {
+        let source: Vec<&str> = [self.source.agent.as_deref(), self.source.project_name()].into_iter().flatten().collect();
+        match (&self.title, source.is_empty()) {
+            (Some(title), false) => format!("{} — {}", title, source.join(" · ")),
+            (Some(title), true) => title.clone(),
+            (None, _) => source.join(" · "),
+        }
+    }
+
+    /// The text the banner shows, for font coverage and direction.
+    pub fn text(&self) -> String {
+        [&self.title, &self.icon, &self.source.agent, &self.source.project, &self.context]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
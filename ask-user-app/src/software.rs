@@ -0,0 +1,186 @@
+use crate::raster::Raster;
+use crate::Dialog;
+use eframe::egui;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::time::Instant;
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::{Window, WindowId};
+
+/// Opens the window described by `viewport` and runs the app `create`
+/// returns until it closes, drawing on the CPU into a plain pixel buffer
+/// for machines where OpenGL can't be set up (VMs, remote desktops,
+/// containers).
+pub fn run<D: Dialog>(
+    viewport: egui::ViewportBuilder,
+    centered: bool,
+    create: impl FnOnce(&egui::Context) -> D,
+) -> eframe::Result<()> {
+    let event_loop = EventLoop::new().map_err(eframe::Error::WinitEventLoop)?;
+    let mut runner = Runner {
+        ctx: egui::Context::default(),
+        viewport,
+        centered,
+        create: Some(create),
+        window: None,
+        error: None,
+        repaint_at: None,
+    };
+    event_loop.run_app(&mut runner).map_err(eframe::Error::WinitEventLoop)?;
+    runner.error.map_or(Ok(()), Err)
+}
+
+struct Runner<D, F> {
+    ctx: egui::Context,
+    viewport: egui::ViewportBuilder,
+    centered: bool,
+    create: Option<F>,
+    window: Option<Open<D>>,
+    error: Option<eframe::Error>,
+    /// When egui last asked to be redrawn, if not straight away.
+    repaint_at: Option<Instant>,
+}
+
+/// Everything that exists once the window does.
+struct Open<D> {
+    window: Rc<Window>,
+    surface: softbuffer::Surface<Rc<Window>, Rc<Window>>,
+    state: egui_winit::State,
+    info: egui::ViewportInfo,
+    app: D,
+    raster: Raster,
+}
+
+fn surface_error(e: softbuffer::SoftBufferError) -> eframe::Error {
+    eframe::Error::AppCreation(Box::new(std::io::Error::other(e.to_string())))
+}
+
+impl<D: Dialog, F: FnOnce(&egui::Context) -> D> Runner<D, F> {
+    fn open(&mut self, event_loop: &ActiveEventLoop) -> eframe::Result<Open<D>> {
+        let window = Rc::new(egui_winit::create_window(&self.ctx, event_loop, &self.viewport)
+            .map_err(eframe::Error::Winit)?);
+        if self.centered {
+            if let Some(monitor) = window.current_monitor() {
+                let (area, size) = (monitor.size(), window.outer_size());
+                let x = monitor.position().x + (area.width as i32 - size.width as i32) / 2;
+                let y = monitor.position().y + (area.height as i32 - size.height as i32) / 2;
+                window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+            }
+        }
+
+        let context = softbuffer::Context::new(window.clone()).map_err(surface_error)?;
+        let surface = softbuffer::Surface::new(&context, window.clone()).map_err(surface_error)?;
+        let state = egui_winit::State::new(
+            self.ctx.clone(),
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            window.theme(),
+            None,
+        );
+        let mut info = egui::ViewportInfo::default();
+        egui_winit::update_viewport_info(&mut info, &self.ctx, &window, true);
+        let create = self.create.take().expect("window opened twice");
+        let app = create(&self.ctx);
+        window.request_redraw();
+        Ok(Open { window, surface, state, info, app, raster: Raster::default() })
+    }
+
+    /// Runs a frame and draws it; false once the app has closed.
+    fn redraw(&mut self) -> eframe::Result<bool> {
+        let Some(open) = &mut self.window else { return Ok(true) };
+        egui_winit::update_viewport_info(&mut open.info, &self.ctx, &open.window, false);
+        let mut input = open.state.take_egui_input(&open.window);
+        input.viewports.insert(egui::ViewportId::ROOT, open.info.clone());
+        open.info.events.clear();
+
+        let app = &mut open.app;
+        let mut output = self.ctx.run(input, |ctx| app.frame(ctx));
+        open.state.handle_platform_output(&open.window, output.platform_output);
+
+        let Some(viewport) = output.viewport_output.remove(&egui::ViewportId::ROOT) else {
+            return Ok(true);
+        };
+        if viewport.commands.contains(&egui::ViewportCommand::Close) {
+            return Ok(false);
+        }
+        egui_winit::process_viewport_commands(
+            &self.ctx,
+            &mut open.info,
+            viewport.commands,
+            &open.window,
+            &mut egui::ahash::HashSet::default(),
+        );
+        self.repaint_at = Instant::now().checked_add(viewport.repaint_delay);
+        if viewport.repaint_delay.is_zero() {
+            open.window.request_redraw();
+        }
+
+        let size = open.window.inner_size();
+        open.raster.upload(&output.textures_delta);
+        let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) else {
+            return Ok(true);
+        };
+        open.surface.resize(width, height).map_err(surface_error)?;
+        let mut buffer = open.surface.buffer_mut().map_err(surface_error)?;
+        let primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+        let background = self.ctx.style().visuals.panel_fill;
+        open.raster.draw(&primitives, output.pixels_per_point, &mut buffer, size.width as usize, background);
+        buffer.present().map_err(surface_error)?;
+        Ok(true)
+    }
+}
+
+impl<D: Dialog, F: FnOnce(&egui::Context) -> D> ApplicationHandler for Runner<D, F> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        match self.open(event_loop) {
+            Ok(open) => self.window = Some(open),
+            Err(e) => {
+                self.error = Some(e);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        let Some(open) = &mut self.window else { return };
+        let response = open.state.on_window_event(&open.window, &event);
+        match event {
+            // The app answers with a Close command once it has recorded
+            // the outcome
+            WindowEvent::CloseRequested => {
+                open.info.events.push(egui::ViewportEvent::Close);
+                open.window.request_redraw();
+            }
+            WindowEvent::RedrawRequested => match self.redraw() {
+                Ok(true) => {}
+                Ok(false) => event_loop.exit(),
+                Err(e) => {
+                    self.error = Some(e);
+                    event_loop.exit();
+                }
+            },
+            _ if response.repaint => open.window.request_redraw(),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        match self.repaint_at {
+            Some(at) if at <= Instant::now() => {
+                self.repaint_at = None;
+                if let Some(open) = &self.window {
+                    open.window.request_redraw();
+                }
+                event_loop.set_control_flow(ControlFlow::Wait);
+            }
+            Some(at) => event_loop.set_control_flow(ControlFlow::WaitUntil(at)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
+    }
+}
@@ -0,0 +1,813 @@
+use super::*;
+use broker::Queue;
+use render::Offscreen;
+use serde_json::json;
+use std::path::Path;
+
+/// Drives a dialog through egui input without a window.
+struct Harness<D = App> {
+    app: D,
+    screen: Offscreen,
+    rx: mpsc::Receiver<Outcome>,
+}
+
+fn appearance(theme: ColorScheme) -> Appearance {
+    Appearance {
+        scale: 1.0,
+        fonts: Vec::new(),
+        fallback_fonts: Vec::new(),
+        // Bundled fonts only, so snapshots match on every machine
+        system_fonts: false,
+        direction: Direction::Ltr,
+        reduced_motion: true,
+        renderer: Renderer::Auto,
+        theme,
+    }
+}
+
+fn parse(input: serde_json::Value) -> InputData {
+    let mut input: InputData = serde_json::from_value(input).unwrap();
+    input.normalize();
+    input
+}
+
+impl Harness {
+    fn new(input: serde_json::Value) -> Self {
+        Self::with_theme(input, ColorScheme::Dark)
+    }
+
+    fn with_theme(input: serde_json::Value, theme: ColorScheme) -> Self {
+        let input = parse(input);
+        let (tx, rx) = mpsc::channel();
+        let screen = Offscreen::new(window::DEFAULT_SIZE);
+        let app = match input.confirm {
+            Some(confirm) => App::confirm(confirm, tx),
+            None => App::new(input.questions, tx).with_review(input.review),
+        };
+        let app = app
+            .with_requester(input.requester)
+            .with_placement(Placement::offscreen(window::DEFAULT_SIZE, false))
+            .with_appearance(&screen.ctx, &appearance(theme));
+        let mut harness = Self { app, screen, rx };
+        harness.settle();
+        harness
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.rx.try_recv().ok()
+    }
+
+    /// The answers the dialog ended with, as they would be printed.
+    fn answers(&self) -> Vec<QuestionAnswer> {
+        match self.outcome() {
+            Some(outcome @ Outcome::Selected(_)) => Response::from_outcome(outcome).answers,
+            Some(other) => panic!("expected answers, got {:?}", Response::from_outcome(other).status),
+            None => panic!("the dialog is still open"),
+        }
+    }
+}
+
+impl<D: Dialog> Harness<D> {
+    fn step(&mut self, events: Vec<egui::Event>) {
+        self.screen.frame(&mut self.app, events);
+    }
+
+    fn settle(&mut self) {
+        self.screen.settle(&mut self.app);
+        self.step(Vec::new());
+    }
+
+    fn press(&mut self, key: egui::Key) {
+        for pressed in [true, false] {
+            self.step(vec![egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            }]);
+        }
+        self.settle();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.step(vec![egui::Event::Text(text.to_string())]);
+        self.settle();
+    }
+
+    /// Where the last frame drew `text`, if it did.
+    fn locate(&self, text: &str) -> Option<egui::Rect> {
+        fn search(shape: &egui::Shape, text: &str) -> Option<egui::Rect> {
+            match shape {
+                egui::Shape::Text(t) if t.galley.text() == text => Some(t.visual_bounding_rect()),
+                egui::Shape::Vec(shapes) => shapes.iter().find_map(|s| search(s, text)),
+                _ => None,
+            }
+        }
+        self.screen.shapes().iter().find_map(|s| search(&s.shape, text))
+    }
+
+    fn find(&self, text: &str) -> egui::Rect {
+        self.locate(text).unwrap_or_else(|| panic!("{:?} is not on screen", text))
+    }
+
+    fn click(&mut self, text: &str) {
+        self.click_at(self.find(text).center());
+    }
+
+    fn click_at(&mut self, pos: egui::Pos2) {
+        let button = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        self.step(vec![egui::Event::PointerMoved(pos)]);
+        self.step(vec![button(true)]);
+        self.step(vec![button(false)]);
+        self.settle();
+    }
+
+    /// Compares the current frame with `src/snapshots/<name>.png`. Set
+    /// `UPDATE_SNAPSHOTS=1` to write new or changed images; otherwise a
+    /// missing snapshot fails and a mismatch is written next to it as
+    /// `<name>.new.png`.
+    fn snapshot(&mut self, name: &str) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots");
+        let path = dir.join(format!("{}.png", name));
+        let (pixels, [width, height]) = self.screen.image();
+        let pixels = pixels.to_vec();
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(&dir).unwrap();
+            self.screen.write_png(&path).unwrap();
+            return;
+        }
+        assert!(path.exists(), "{} has no snapshot; run with UPDATE_SNAPSHOTS=1 to create it", name);
+
+        let decoder = png::Decoder::new(fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut expected = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut expected).unwrap();
+        let same_size = [info.width as usize, info.height as usize] == [width, height];
+        // Allow a few pixels of antialiasing noise, nothing that moved
+        let differing = pixels.iter().zip(expected.chunks_exact(3))
+            .filter(|(p, e)| {
+                let [_, r, g, b] = p.to_be_bytes();
+                [r, g, b].iter().zip(e.iter()).any(|(a, b)| a.abs_diff(*b) > 8)
+            })
+            .count();
+        if !same_size || differing > pixels.len() / 1000 {
+            let new = dir.join(format!("{}.new.png", name));
+            self.screen.write_png(&new).unwrap();
+            panic!(
+                "{} differs from its snapshot ({}x{}, {} pixels changed); see {}",
+                name, width, height, differing, new.display(),
+            );
+        }
+    }
+}
+
+fn one_question() -> serde_json::Value {
+    json!({"questions": [{
+        "question": "Which database?",
+        "header": "Database",
+        "options": [
+            {"label": "PostgreSQL", "description": "Relational, full featured"},
+            {"label": "SQLite", "description": "Embedded, single file"},
+            {"label": "MySQL"}
+        ]
+    }]})
+}
+
+fn two_questions() -> serde_json::Value {
+    json!({"questions": [
+        {"question": "Which database?", "header": "Database", "options": [{"label": "PostgreSQL"}, {"label": "SQLite"}]},
+        {"question": "Which cache?", "header": "Cache", "options": [{"label": "Redis"}, {"label": "None"}]}
+    ]})
+}
+
+#[test]
+fn click_picks_an_option_and_review_submits_it() {
+    let mut h = Harness::new(one_question());
+    h.click("SQLite");
+    h.press(egui::Key::Enter);
+    assert!(matches!(h.app.screen, Screen::Review));
+    h.press(egui::Key::Enter);
+
+    let answers = h.answers();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].selected, Some(json!("SQLite")));
+    assert_eq!(answers[0].selected_index, Some(json!(1)));
+    assert_eq!(answers[0].other, None);
+}
+
+#[test]
+fn single_select_keeps_one_option() {
+    let mut h = Harness::new(one_question());
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Num3);
+    assert_eq!(h.app.get_selection(), (vec!["MySQL".to_string()], vec![2]));
+}
+
+#[test]
+fn number_keys_toggle_multi_select() {
+    let mut input = one_question();
+    input["questions"][0]["multiSelect"] = json!(true);
+    let mut h = Harness::new(input);
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Num3);
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+
+    let answers = h.answers();
+    assert_eq!(answers[0].selected, Some(json!(["PostgreSQL", "MySQL"])));
+    assert_eq!(answers[0].selected_index, Some(json!([0, 2])));
+}
+
+#[test]
+fn enter_needs_a_selection() {
+    let mut h = Harness::new(one_question());
+    h.press(egui::Key::Enter);
+    assert!(matches!(h.app.screen, Screen::Question(0)));
+    assert!(h.app.answers.is_empty());
+    assert!(h.outcome().is_none());
+}
+
+#[test]
+fn escape_goes_back_and_drops_the_saved_answer() {
+    let mut h = Harness::new(two_questions());
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    assert!(matches!(h.app.screen, Screen::Question(1)));
+    assert_eq!(h.app.answers.len(), 1);
+
+    h.press(egui::Key::Escape);
+    assert!(matches!(h.app.screen, Screen::Question(0)));
+    assert!(h.app.answers.is_empty());
+
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    // Back from review reopens the last question, unanswered
+    h.press(egui::Key::Escape);
+    assert!(matches!(h.app.screen, Screen::Question(1)));
+    assert!(!h.app.has_selection());
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+
+    let answers = h.answers();
+    let selected: Vec<_> = answers.iter().map(|a| a.selected.clone()).collect();
+    assert_eq!(selected, [Some(json!("SQLite")), Some(json!("Redis"))]);
+}
+
+#[test]
+fn escape_on_the_first_question_cancels() {
+    let mut h = Harness::new(two_questions());
+    h.press(egui::Key::Escape);
+    assert!(matches!(h.outcome(), Some(Outcome::Cancelled)));
+}
+
+#[test]
+fn typing_into_other_chooses_it() {
+    let mut h = Harness::new(one_question());
+    h.press(egui::Key::Num1);
+    h.click("Type a custom response...");
+    h.type_text("  CockroachDB ");
+    assert!(h.app.other);
+    assert!(!h.app.selected.iter().any(|&s| s));
+
+    h.click("Review");
+    h.press(egui::Key::Enter);
+    let answers = h.answers();
+    assert_eq!(answers[0].selected, None);
+    assert_eq!(answers[0].other.as_deref(), Some("CockroachDB"));
+}
+
+#[test]
+fn picking_an_option_after_other_drops_its_text() {
+    let mut h = Harness::new(one_question());
+    h.click("Type a custom response...");
+    h.type_text("CockroachDB");
+    h.click("PostgreSQL");
+    assert!(!h.app.other);
+    assert_eq!(h.app.custom, "CockroachDB");
+
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+    let answers = h.answers();
+    assert_eq!(answers[0].selected, Some(json!("PostgreSQL")));
+    assert_eq!(answers[0].other, None);
+}
+
+#[test]
+fn defaults_are_preselected_and_reported() {
+    let mut input = one_question();
+    input["questions"][0]["options"][1]["default"] = json!(true);
+    let mut h = Harness::new(input);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+
+    let answers = h.answers();
+    assert_eq!(answers[0].selected, Some(json!("SQLite")));
+    assert_eq!(answers[0].accepted_default, Some(true));
+}
+
+#[test]
+fn snapshot_question() {
+    let mut h = Harness::new(one_question());
+    h.press(egui::Key::Num2);
+    h.snapshot("question");
+}
+
+#[test]
+fn snapshot_question_light() {
+    let mut input = one_question();
+    input["questions"][0]["multiSelect"] = json!(true);
+    let mut h = Harness::with_theme(input, ColorScheme::Light);
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Num3);
+    h.snapshot("question_light");
+}
+
+#[test]
+fn snapshot_review() {
+    let mut h = Harness::new(two_questions());
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Enter);
+    h.snapshot("review");
+}
+
+#[test]
+fn snapshot_confirm() {
+    let mut h = Harness::new(json!({"confirm": {
+        "title": "Delete the build directory?",
+        "body": "This removes everything under target/.",
+        "command": "rm -rf target",
+        "danger": true
+    }}));
+    h.snapshot("confirm");
+}
+
+#[test]
+fn config_keeps_the_settings_it_can_read() {
+    let path = Path::new("config.json");
+    let config = Config::parse(r#"{"scale": "big", "theme": "light", "audit": {"enabled": "yes"}}"#, path);
+    assert_eq!(config.scale, None);
+    assert!(matches!(config.theme, Some(ColorScheme::Light)));
+    assert!(!config.audit.enabled && config.audit_error.is_some());
+
+    let config = Config::parse("{\"scale\": 1.5,", path);
+    assert_eq!(config.scale, None);
+    assert!(config.audit_error.is_some());
+    assert!(Config::parse(r#"{"scale": 1.5}"#, path).audit_error.is_none());
+}
+
+#[test]
+fn keyed_answers_keep_shared_headers_apart() {
+    let answer = |question: &str, header: &str, selected: &str| -> QuestionAnswer {
+        serde_json::from_value(json!({"question": question, "header": header, "selected": selected})).unwrap()
+    };
+    let response = Response {
+        status: "selected".into(),
+        answers: vec![
+            answer("Which database?", "Choice", "Postgres"),
+            answer("Which cache?", "Choice", "Redis"),
+            answer("Which cache?", "Choice", "Memcached"),
+        ],
+    };
+    assert_eq!(
+        response.to_json(AnswersFormat::Keyed)["answers"],
+        json!({"Choice": "Postgres", "Which cache?": "Redis", "Which cache? (2)": "Memcached"}),
+    );
+}
+
+#[test]
+fn path_filters_match_globs() {
+    assert!(pathpick::glob_match("*.{yml,yaml}", "CI.YAML"));
+    assert!(pathpick::glob_match("*.rs; Cargo.?oml", "Cargo.toml"));
+    assert!(!pathpick::glob_match("*.rs", "main.rs.bak"));
+    assert!(pathpick::glob_match("a*b*c", "aXbYbZc"));
+    // Would take far too long with a backtracking matcher
+    assert!(!pathpick::glob_match("*a*a*a*a*a*a*a*a*b", &"a".repeat(60)));
+}
+
+#[test]
+fn path_answers_are_checked_again_before_moving_on() {
+    let dir = std::env::temp_dir().join(format!("askuserquestion-recheck-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut h = Harness::new(json!({"questions": [
+        {"type": "path", "question": "Which manifest?", "header": "Manifest", "baseDir": dir},
+    ]}));
+    h.app.apply(Event::SetCustom { text: "app.toml".into() });
+    h.settle();
+    assert!(!h.app.has_selection());
+    // Created after the field was checked
+    fs::write(dir.join("app.toml"), "").unwrap();
+    h.app.apply(Event::Next);
+    let _ = fs::remove_dir_all(&dir);
+    assert!(matches!(h.app.screen, Screen::Review));
+}
+
+#[test]
+fn bidi_keeps_marks_on_their_letters() {
+    // Shin with qamats, then lamed: the mark stays after its letter
+    assert_eq!(bidi::visual("\u{5E9}\u{5B8}\u{5DC}"), "\u{5DC}\u{5E9}\u{5B8}");
+    assert_eq!(bidi::visual("abc (\u{5D0}\u{5D1})"), "abc (\u{5D1}\u{5D0})");
+}
+
+#[test]
+fn software_rerun_replaces_the_renderer() {
+    let args = ["--renderer", "auto", "--input", "q.json", "--renderer=gl", "--record", "t.jsonl"].map(Into::into);
+    let rerun: Vec<String> = software_args(args).iter().map(|a| a.to_string_lossy().into_owned()).collect();
+    assert_eq!(rerun, ["--renderer", "software", "--input", "q.json", "--record", "t.jsonl"]);
+}
+
+#[test]
+fn review_render_fills_in_every_question() {
+    let out = std::env::temp_dir().join(format!("askuserquestion-review-{}.png", std::process::id()));
+    let render = |base: &str| {
+        let input = parse(json!({"questions": [
+            {"type": "path", "question": "Which manifest?", "header": "Manifest", "baseDir": base, "filter": "*.toml"},
+            {"question": "Which region?", "header": "Region", "options": [{"label": "eu-west-1"}]},
+        ]}));
+        render::render(&input, render::Shown::Review, 420.0, None, &appearance(ColorScheme::Dark), &out)
+    };
+    assert_eq!(render(env!("CARGO_MANIFEST_DIR")), Ok(()));
+    assert_eq!(render("/nonexistent"), Err("no answer to fill in for question 1 on the review screen".to_string()));
+    let _ = fs::remove_file(&out);
+}
+
+impl broker::Reply for mpsc::Sender<Outcome> {
+    fn answer(self, outcome: Outcome) {
+        let _ = self.send(outcome);
+    }
+}
+
+#[test]
+fn queued_callers_each_get_their_own_outcome() {
+    let request = |agent: &str, mut input: serde_json::Value| {
+        input["agentId"] = json!(agent);
+        broker::Request { input: parse(input), appearance: appearance(ColorScheme::Dark), position: None, monitor: None, cwd: None }
+    };
+    let (planner_tx, planner) = mpsc::channel();
+    let (reviewer_tx, reviewer) = mpsc::channel();
+    let (queue_tx, incoming) = mpsc::channel();
+    let screen = Offscreen::new(window::DEFAULT_SIZE);
+    let placement = Placement::offscreen(window::DEFAULT_SIZE, false);
+    let queue = Queue::new(&screen.ctx, (request("planner", one_question()), planner_tx), placement, incoming);
+    let mut h = Harness { app: queue, screen, rx: planner };
+    h.settle();
+    // The reviewer brings a font of its own
+    let font = std::env::temp_dir().join(format!("askuserquestion-font-{}.ttf", std::process::id()));
+    fs::write(&font, &*egui::FontDefinitions::default().font_data["Hack"].font).unwrap();
+    let cache = json!({"questions": [{"question": "Which cache?", "header": "Cache", "options": [{"label": "Redis"}, {"label": "None"}]}]});
+    let mut reviewing = request("reviewer", cache);
+    reviewing.appearance.fonts = vec![font.clone()];
+    queue_tx.send((reviewing, reviewer_tx)).unwrap();
+    h.settle();
+    let _ = fs::remove_file(&font);
+    let custom_fonts = |h: &Harness<Queue<mpsc::Sender<Outcome>>>| h.screen.ctx.fonts(|f| f.lock().fonts.definitions().font_data.contains_key("user-0"));
+
+    h.snapshot("queue");
+    assert!(!custom_fonts(&h));
+    h.click("reviewer");
+    h.find("Which cache?");
+    assert!(custom_fonts(&h));
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+    let Ok(Outcome::Selected(answers)) = reviewer.try_recv() else { panic!("the reviewer got no answers") };
+    assert_eq!(Response::from_outcome(Outcome::Selected(answers)).answers[0].selected, Some(json!("Redis")));
+    assert!(h.rx.try_recv().is_err());
+
+    // Back to one caller, so no tabs
+    h.find("Which database?");
+    assert!(h.locate("reviewer").is_none());
+    assert!(!custom_fonts(&h));
+    h.press(egui::Key::Num3);
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+    let Ok(Outcome::Selected(answers)) = h.rx.try_recv() else { panic!("the planner got no answers") };
+    assert_eq!(Response::from_outcome(Outcome::Selected(answers)).answers[0].selected, Some(json!("MySQL")));
+}
+
+#[test]
+fn queued_paths_resolve_against_each_callers_directory() {
+    let root = std::env::temp_dir().join(format!("askuserquestion-cwd-{}", std::process::id()));
+    let project = |name: &str, file: &str| {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file), "").unwrap();
+        fs::write(dir.join("logo.png"), png_bytes(8, 8, [200, 40, 40])).unwrap();
+        dir
+    };
+    let (web, api) = (project("web", "web.toml"), project("api", "api.toml"));
+    let request = |agent: &str, cwd: &Path| broker::Request {
+        input: parse(json!({
+            "agentId": agent,
+            "questions": [{"type": "path", "question": "Which manifest?", "header": "Manifest", "image": "logo.png"}],
+        })),
+        appearance: appearance(ColorScheme::Dark),
+        position: None,
+        monitor: None,
+        cwd: Some(cwd.to_path_buf()),
+    };
+    let (web_tx, web_rx) = mpsc::channel();
+    let (api_tx, api_rx) = mpsc::channel();
+    let (queue_tx, incoming) = mpsc::channel();
+    let screen = Offscreen::new(window::DEFAULT_SIZE);
+    let placement = Placement::offscreen(window::DEFAULT_SIZE, false);
+    let queue = Queue::new(&screen.ctx, (request("web", &web), web_tx), placement, incoming);
+    let mut h = Harness { app: queue, screen, rx: web_rx };
+    h.settle();
+    queue_tx.send((request("api", &api), api_tx)).unwrap();
+    h.settle();
+
+    let pick = |h: &mut Harness<Queue<mpsc::Sender<Outcome>>>, file: &str| {
+        // The image is read from the caller's directory too
+        assert!(h.locate("not found").is_none());
+        h.click(file);
+        h.press(egui::Key::Enter);
+        // A click that counted as a double click has moved on already
+        if h.locate("Review your answers").is_some() {
+            h.press(egui::Key::Enter);
+        }
+    };
+    h.click("api");
+    assert!(h.locate("web.toml").is_none());
+    pick(&mut h, "api.toml");
+    let Ok(Outcome::Selected(api_answers)) = api_rx.try_recv() else { panic!("the api caller got no answer") };
+    pick(&mut h, "web.toml");
+    let Ok(Outcome::Selected(web_answers)) = h.rx.try_recv() else { panic!("the web caller got no answer") };
+    let expected = |dir: &Path, file: &str| Some(dir.canonicalize().unwrap().join(file).display().to_string());
+    let (api_expected, web_expected) = (expected(&api, "api.toml"), expected(&web, "web.toml"));
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(api_answers[0].path, api_expected);
+    assert_eq!(web_answers[0].path, web_expected);
+}
+
+#[test]
+fn markdown_spans() {
+    use markdown::Span;
+    let text = |text: &str, strong, italic| Span::Text { text: text.to_string(), strong, italic };
+    assert_eq!(markdown::spans("Use **pnpm**, not `npm` or my_snake_case *here*"), [
+        text("Use ", false, false),
+        text("pnpm", true, false),
+        text(", not ", false, false),
+        Span::Code("npm".to_string()),
+        text(" or my_snake_case ", false, false),
+        text("here", false, true),
+    ]);
+    assert_eq!(markdown::spans("See [the docs](https://pnpm.io) * 2"), [
+        text("See ", false, false),
+        Span::Link { text: "the docs".to_string(), url: "https://pnpm.io".to_string() },
+        text(" * 2", false, false),
+    ]);
+}
+
+#[test]
+fn snapshot_banner() {
+    let mut input = one_question();
+    input["title"] = json!("Set up CI");
+    input["agentId"] = json!("build");
+    input["source"] = json!({"project": "/work/shop"});
+    input["context"] = json!("The repo has both `npm` and `pnpm` lockfiles.\n\n- CI needs **one**\n- See [the docs](https://pnpm.io)");
+    let mut h = Harness::new(input);
+    assert_eq!(h.app.requester.window_title(), "Set up CI — build · shop");
+    h.snapshot("banner");
+}
+
+/// A solid `width`x`height` PNG.
+fn png_bytes(width: u32, height: u32, rgb: [u8; 3]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgb.repeat((width * height) as usize)).unwrap();
+    writer.finish().unwrap();
+    bytes
+}
+
+/// The same PNG as a data URI.
+fn png_data(width: u32, height: u32, rgb: [u8; 3]) -> String {
+    let bytes = png_bytes(width, height, rgb);
+    format!("data:image/png;base64,{}", base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes))
+}
+
+#[test]
+fn images_show_with_placeholders_and_zoom() {
+    let mut h = Harness::new(json!({"questions": [{
+        "question": "Which logo?",
+        "header": "Logo",
+        "image": png_data(320, 90, [60, 70, 90]),
+        "options": [
+            {"label": "Round", "description": "Soft corners", "image": png_data(120, 90, [220, 120, 40])},
+            {"label": "Square", "image": "/nonexistent/square.png"},
+        ],
+    }]}));
+    assert!(h.locate("not found").is_some());
+    h.snapshot("images");
+
+    // Clicking a thumbnail zooms it rather than picking its option
+    let thumbnail = h.screen.shapes().iter()
+        .filter_map(|s| match &s.shape {
+            egui::Shape::Rect(rect) if rect.fill_texture_id() != egui::TextureId::default() => Some(rect.rect),
+            _ => None,
+        })
+        .find(|rect| rect.center().y > h.find("Round").top())
+        .expect("the option's thumbnail is drawn");
+    h.click_at(thumbnail.center());
+    assert!(h.app.zoomed.is_some());
+    assert_eq!(h.app.selected, [false, false]);
+
+    // Keys close the picture before they reach the dialog
+    h.press(egui::Key::Escape);
+    assert!(h.app.zoomed.is_none());
+    assert!(h.outcome().is_none());
+    h.click("Round");
+    assert_eq!(h.app.selected, [true, false]);
+}
+
+#[test]
+fn matrix_answers_each_row() {
+    let mut h = Harness::new(json!({"questions": [{
+        "type": "matrix",
+        "question": "What should happen to each service?",
+        "header": "Services",
+        "rows": [{"label": "auth"}, {"label": "billing"}, {"label": "legacy", "value": "reports"}],
+        "options": [
+            {"label": "Keep", "value": "keep"},
+            {"label": "Migrate", "value": "migrate"},
+            {"label": "Delete", "value": "delete"},
+        ],
+    }]}));
+    h.snapshot("matrix");
+
+    // Number keys fill in the first row still unanswered
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Num1);
+    assert!(!h.app.has_selection());
+    h.press(egui::Key::Enter);
+    assert!(matches!(h.app.screen, Screen::Question(0)));
+
+    let cell = egui::pos2(h.find("Delete").center().x, h.find("legacy").center().y);
+    h.click_at(cell);
+    assert!(h.app.has_selection());
+    // One choice per row
+    h.click_at(egui::pos2(h.find("Keep").center().x, h.find("legacy").center().y));
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Enter);
+
+    let response = Response::from_outcome(h.outcome().unwrap());
+    let answer = &response.answers[0];
+    assert_eq!(answer.selected, Some(json!({"auth": "Migrate", "billing": "Keep", "reports": "Keep"})));
+    assert_eq!(answer.selected_index, Some(json!({"auth": 1, "billing": 0, "reports": 0})));
+    assert_eq!(
+        response.to_json(AnswersFormat::Keyed)["answers"]["Services"],
+        json!({"auth": "migrate", "billing": "keep", "reports": "keep"}),
+    );
+
+    // Rows are keyed by value, else label, and must not share a key
+    let repeated = parse(json!({"questions": [{
+        "type": "matrix",
+        "question": "What should happen to each service?",
+        "rows": [{"label": "auth"}, {"label": "legacy", "value": "reports"}, {"label": "reports"}],
+        "options": [{"label": "Keep"}],
+    }]}));
+    assert_eq!(repeated.questions[0].repeated_row_key(), Some("reports"));
+    assert_eq!(h.app.questions[0].repeated_row_key(), None);
+}
+
+#[test]
+fn dates_and_durations_answer_in_iso_8601() {
+    let mut h = Harness::new(json!({"questions": [
+        {
+            "type": "datetime",
+            "question": "When should the migration run?",
+            "header": "Window",
+            "timezone": "Europe/Paris",
+            "min": "2031-03-10 00:00",
+            "max": "2031-03-28 23:59",
+        },
+        {"type": "duration", "question": "How long should the cache live?", "header": "TTL", "max": "7d"},
+    ]}));
+    // The calendar opens on the earliest allowed day
+    h.click("20");
+    assert_eq!(h.app.custom, "2031-03-20 00:00");
+    h.snapshot("datetime");
+    h.press(egui::Key::Enter);
+
+    h.click("e.g. 1h 30m");
+    h.type_text("8d");
+    assert!(h.locate("At most 7d").is_some());
+    assert!(!h.app.has_selection());
+    h.press(egui::Key::Backspace);
+    h.press(egui::Key::Backspace);
+    h.type_text("90m");
+    h.click("Review");
+    h.press(egui::Key::Enter);
+
+    let answers = h.answers();
+    assert_eq!(answers[0].selected, Some(json!("2031-03-20T00:00:00+01:00")));
+    assert_eq!(answers[1].selected, Some(json!("PT1H30M")));
+}
+
+#[test]
+fn secrets_are_masked_and_kept_out_of_records() {
+    let mut h = Harness::new(json!({"questions": [
+        {"type": "secret", "id": "token", "question": "Paste the deploy token", "header": "Token"},
+        {"question": "Which region?", "header": "Region", "options": [{"label": "eu-west-1"}, {"label": "us-east-1"}]},
+    ]}));
+    let trace = std::env::temp_dir().join(format!("askuserquestion-secret-{}.jsonl", std::process::id()));
+    h.app.recorder = Some(Recorder::create(trace.to_str().unwrap()).unwrap());
+
+    h.click("Type or paste it here...");
+    h.type_text("hunter2");
+    assert!(h.locate("hunter2").is_none());
+    h.click("Show");
+    assert!(h.locate("hunter2").is_some());
+    h.snapshot("secret");
+    assert_eq!(h.app.snapshot().custom, "");
+    h.press(egui::Key::Enter);
+
+    // The draft keeps the secret's slot, empty, to be asked again
+    let draft = h.app.snapshot();
+    assert_eq!((draft.screen, draft.answers.len()), (1, 1));
+    assert!(draft.answers[0].is_wiped());
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    assert!(h.locate(SECRET_MASK).is_some());
+    assert!(h.locate("hunter2").is_none());
+    let draft = h.app.snapshot();
+    assert_eq!(draft.screen, 2);
+    assert!(draft.answers[0].is_wiped());
+    assert_eq!(draft.answers[1].selected, ["eu-west-1"]);
+
+    // Restoring it asks only for the secret, then goes back to the review
+    let mut restored = Harness::new(json!({"questions": [
+        {"type": "secret", "id": "token", "question": "Paste the deploy token", "header": "Token"},
+        {"question": "Which region?", "header": "Region", "options": [{"label": "eu-west-1"}, {"label": "us-east-1"}]},
+    ]}));
+    restored.app.apply(Event::Restore { draft });
+    restored.settle();
+    assert!(restored.locate("Paste the deploy token").is_some());
+    restored.click("Type or paste it here...");
+    restored.type_text("swordfish");
+    restored.press(egui::Key::Enter);
+    assert!(restored.locate("Review your answers").is_some());
+    restored.press(egui::Key::Enter);
+    let answers = restored.answers();
+    assert_eq!(answers[0].selected, Some(json!("swordfish")));
+    assert_eq!(answers[1].selected, Some(json!("eu-west-1")));
+    h.press(egui::Key::Enter);
+
+    let response = Response::from_outcome(h.outcome().unwrap());
+    assert_eq!(response.answers[0].selected, Some(json!("hunter2")));
+    assert_eq!(response.redacted().answers[0].selected, None);
+    assert!(h.app.custom.is_empty() && h.app.answers[0].selected.iter().all(String::is_empty));
+    let recorded = fs::read_to_string(&trace).unwrap();
+    assert!(!recorded.contains("hunter2"));
+
+    // Replays answer with the placeholder and keep later answers in line
+    let events: Vec<(u64, Event)> = recorded.lines()
+        .filter_map(|line| match serde_json::from_str(line).unwrap() {
+            record::TraceEntry::Event { t_ms, event } => Some((t_ms, event)),
+            _ => None,
+        })
+        .collect();
+    let _ = fs::remove_file(&trace);
+    let questions = h.app.questions.clone();
+    let Outcome::Selected(answers) = replay_headless(|tx| App::new(questions, tx), &events) else { panic!("the replay didn't finish") };
+    assert_eq!(answers.iter().map(|a| a.selected.clone()).collect::<Vec<_>>(), [vec![SECRET_PLACEHOLDER.to_string()], vec!["eu-west-1".to_string()]]);
+}
+
+#[test]
+fn closing_a_restored_draft_stops_at_the_secret() {
+    let input = json!({"questions": [
+        {"question": "Which cloud?", "header": "Cloud", "options": [{"label": "AWS"}, {"label": "GCP"}]},
+        {"type": "secret", "question": "Paste the deploy token", "header": "Token"},
+        {"question": "Which region?", "header": "Region", "options": [{"label": "eu-west-1"}, {"label": "us-east-1"}]},
+    ]});
+    let mut h = Harness::new(input.clone());
+    h.press(egui::Key::Num2);
+    h.press(egui::Key::Enter);
+    h.click("Type or paste it here...");
+    h.type_text("hunter2");
+    h.press(egui::Key::Enter);
+    h.press(egui::Key::Num1);
+    h.press(egui::Key::Enter);
+    let draft = h.app.snapshot();
+
+    // The region can't move up into the secret's place
+    let mut restored = Harness::new(input);
+    restored.app.apply(Event::Restore { draft });
+    restored.app.apply(Event::Close);
+    let Some(Outcome::Partial(answers)) = restored.outcome() else { panic!("expected a partial outcome") };
+    assert_eq!(answers.iter().map(|a| a.selected.clone()).collect::<Vec<_>>(), [vec!["GCP".to_string()]]);
+}
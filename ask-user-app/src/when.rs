@@ -0,0 +1,198 @@
+use crate::{Question, QuestionKind};
+use jiff::civil::{Date, DateTime, Time};
+use jiff::tz::TimeZone;
+use jiff::{Span, SpanTotal, Timestamp, Unit, Zoned};
+
+/// An answer to a date, time, datetime or duration question.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum When {
+    Date(Date),
+    Time(Time),
+    /// Wall-clock time in the question's time zone.
+    DateTime(DateTime),
+    /// Whole seconds.
+    Duration(i64),
+}
+
+/// The zone datetime answers are given in: the question's, else the
+/// system's.
+pub fn zone(q: &Question) -> TimeZone {
+    q.timezone.as_deref().and_then(|name| TimeZone::get(name).ok()).unwrap_or_else(TimeZone::system)
+}
+
+/// "Europe/Paris (UTC+02:00)", for showing beside the time.
+pub fn zone_label(q: &Question, at: DateTime) -> String {
+    let tz = zone(q);
+    let offset = at.to_zoned(tz.clone()).map(|z| z.offset().to_string()).unwrap_or_default();
+    match tz.iana_name() {
+        Some(name) => format!("{} (UTC{})", name, offset),
+        None => format!("UTC{}", offset),
+    }
+}
+
+/// `H:MM` or `H:MM:SS`, more forgiving than ISO 8601's `HH:MM`.
+fn time(text: &str) -> Option<Time> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|p| p.is_empty() || p.len() > 2) {
+        return None;
+    }
+    let n: Vec<i8> = parts.iter().map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    Time::new(n[0], n[1], n.get(2).copied().unwrap_or(0), 0).ok()
+}
+
+fn parse(q: &Question, text: &str) -> Result<When, String> {
+    let text = text.trim();
+    match q.kind {
+        QuestionKind::Date => text.parse().map(When::Date).map_err(|_| "Use YYYY-MM-DD, e.g. 2026-03-14".into()),
+        QuestionKind::Time => time(text).map(When::Time).ok_or_else(|| "Use HH:MM, e.g. 09:30".into()),
+        QuestionKind::DateTime => {
+            // With an offset it's a moment, shown in the question's zone
+            if let Ok(ts) = text.parse::<Timestamp>() {
+                return Ok(When::DateTime(ts.to_zoned(zone(q)).datetime()));
+            }
+            let parsed = text.split_once(['T', ' ']).and_then(|(date, t)| {
+                Some(DateTime::from_parts(date.parse().ok()?, time(t.trim())?))
+            });
+            parsed.map(When::DateTime).ok_or_else(|| "Use YYYY-MM-DD HH:MM, e.g. 2026-03-14 09:30".into())
+        }
+        QuestionKind::Duration => {
+            let unreadable = || "Use e.g. 1h 30m, 90s or PT1H30M".to_string();
+            let span: Span = text.parse().map_err(|_| unreadable())?;
+            let secs = span.total(SpanTotal::from(Unit::Second).days_are_24_hours()).map_err(|_| unreadable())?;
+            if secs < 0.0 {
+                return Err("Can't be negative".into());
+            }
+            Ok(When::Duration(secs.round() as i64))
+        }
+        _ => Err(String::new()),
+    }
+}
+
+/// Checks `text` is readable and within the question's bounds.
+pub fn check(q: &Question, text: &str) -> Result<When, String> {
+    if text.trim().is_empty() {
+        return Err(String::new());
+    }
+    let value = parse(q, text)?;
+    let (min, max) = bounds(q);
+    let later = if q.kind == QuestionKind::Duration { "At least" } else { "Earliest is" };
+    let earlier = if q.kind == QuestionKind::Duration { "At most" } else { "Latest is" };
+    match (min, max) {
+        (Some(min), _) if value < min => Err(format!("{} {}", later, self::text(&min))),
+        (_, Some(max)) if value > max => Err(format!("{} {}", earlier, self::text(&max))),
+        _ => Ok(value),
+    }
+}
+
+/// `min` and `max`, where readable.
+pub fn bounds(q: &Question) -> (Option<When>, Option<When>) {
+    let bound = |b: &Option<String>| b.as_deref().and_then(|b| parse(q, b).ok());
+    (bound(&q.min), bound(&q.max))
+}
+
+/// Why the question's bounds or time zone can't be used, if they can't.
+pub fn validate(q: &Question) -> Result<(), String> {
+    if let Some(name) = &q.timezone {
+        TimeZone::get(name).map_err(|_| format!("unknown time zone {:?}", name))?;
+    }
+    for bound in [&q.min, &q.max, &q.default].into_iter().flatten() {
+        parse(q, bound).map_err(|_| format!("can't read {:?} as a {}", bound, kind_name(q.kind)))?;
+    }
+    Ok(())
+}
+
+fn kind_name(kind: QuestionKind) -> &'static str {
+    match kind {
+        QuestionKind::Date => "date",
+        QuestionKind::Time => "time",
+        QuestionKind::DateTime => "date and time",
+        _ => "duration",
+    }
+}
+
+/// What the picker shows before anything is entered: the question's
+/// default, else now (or nothing, for durations), kept within bounds.
+pub fn start(q: &Question) -> When {
+    if let Some(value) = q.default.as_deref().and_then(|d| parse(q, d).ok()) {
+        return value;
+    }
+    let now = Zoned::now().with_time_zone(zone(q)).datetime();
+    let now = match q.kind {
+        QuestionKind::Date => When::Date(now.date()),
+        QuestionKind::Time => When::Time(Time::new(now.hour(), now.minute(), 0, 0).unwrap_or_default()),
+        QuestionKind::DateTime => When::DateTime(now.with().second(0).subsec_nanosecond(0).build().unwrap_or(now)),
+        _ => When::Duration(0),
+    };
+    match bounds(q) {
+        (Some(min), _) if now < min => min,
+        (_, Some(max)) if now > max => max,
+        _ => now,
+    }
+}
+
+/// The day `value` falls on, for date and datetime answers.
+pub fn day(value: &When) -> Option<Date> {
+    match value {
+        When::Date(date) => Some(*date),
+        When::DateTime(dt) => Some(dt.date()),
+        _ => None,
+    }
+}
+
+/// How `value` is written into the text field.
+pub fn text(value: &When) -> String {
+    match value {
+        When::Date(date) => date.to_string(),
+        When::Time(time) => clock(time),
+        When::DateTime(dt) => format!("{} {}", dt.date(), clock(&dt.time())),
+        When::Duration(secs) => {
+            let parts: Vec<String> = units(*secs).into_iter()
+                .zip(["d", "h", "m", "s"])
+                .filter(|(n, _)| *n > 0)
+                .map(|(n, unit)| format!("{}{}", n, unit))
+                .collect();
+            if parts.is_empty() { "0s".into() } else { parts.join(" ") }
+        }
+    }
+}
+
+/// `HH:MM`, with seconds only when there are some.
+fn clock(time: &Time) -> String {
+    if time.second() == 0 { time.strftime("%H:%M").to_string() } else { time.strftime("%H:%M:%S").to_string() }
+}
+
+/// Days, hours, minutes and seconds in `secs`.
+pub fn units(secs: i64) -> [i64; 4] {
+    [secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60]
+}
+
+/// The answer as ISO 8601: `2026-03-14`, `09:30`, `2026-03-14T09:30:00+01:00`
+/// or `PT1H30M`.
+pub fn iso(q: &Question, value: &When) -> String {
+    match value {
+        When::Date(_) | When::Time(_) => text(value),
+        When::DateTime(dt) => match dt.to_zoned(zone(q)) {
+            Ok(zoned) => zoned.timestamp().display_with_offset(zoned.offset()).to_string(),
+            Err(_) => dt.to_string(),
+        },
+        When::Duration(secs) => {
+            let [d, h, m, s] = units(*secs);
+            let mut out = String::from("P");
+            if d > 0 {
+                out += &format!("{}D", d);
+            }
+            if h > 0 || m > 0 || s > 0 || d == 0 {
+                out.push('T');
+                for (n, unit) in [(h, 'H'), (m, 'M'), (s, 'S')] {
+                    if n > 0 {
+                        out += &format!("{}{}", n, unit);
+                    }
+                }
+                if out == "PT" {
+                    out.push_str("0S");
+                }
+            }
+            out
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::paths;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Size used until the content has been measured.
+pub const DEFAULT_SIZE: egui::Vec2 = egui::vec2(420.0, 520.0);
+pub const MIN_SIZE: egui::Vec2 = egui::vec2(360.0, 240.0);
+/// Gap kept between the window and the edges of the display.
+const MARGIN: f32 = 24.0;
+
+/// Where on the display the window opens (`--position`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Position {
+    Center,
+    Cursor,
+    TopRight,
+    /// Offset from the display's top-left corner.
+    At(f32, f32),
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "center" => Ok(Self::Center),
+            "cursor" => Ok(Self::Cursor),
+            "top-right" => Ok(Self::TopRight),
+            _ => {
+                let parsed = s.split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+                parsed.map(|(x, y)| Self::At(x, y))
+                    .ok_or_else(|| "expected center, cursor, top-right or X,Y".to_string())
+            }
+        }
+    }
+}
+
+/// Which display the window opens on (`--monitor`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MonitorChoice {
+    Primary,
+    /// 1-based, in the order the OS lists displays.
+    Index(usize),
+}
+
+impl FromStr for MonitorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "primary" => Ok(Self::Primary),
+            _ => s.parse().ok().filter(|&n| n > 0).map(Self::Index)
+                .ok_or_else(|| "expected primary or a display number starting at 1".to_string()),
+        }
+    }
+}
+
+struct Monitor {
+    rect: egui::Rect,
+    scale: f32,
+    primary: bool,
+}
+
+impl Monitor {
+    /// Remembered geometry is filed under the display's layout, which is
+    /// stable across runs where ids are not.
+    fn key(&self) -> String {
+        format!("{}x{}+{}+{}", self.rect.width(), self.rect.height(), self.rect.min.x, self.rect.min.y)
+    }
+}
+
+fn monitors() -> Vec<Monitor> {
+    let Ok(displays) = display_info::DisplayInfo::all() else { return Vec::new() };
+    displays.into_iter().map(|d| {
+        // Windows reports physical pixels; the others already use points
+        let scale = if cfg!(windows) { d.scale_factor.max(1.0) } else { 1.0 };
+        Monitor {
+            rect: egui::Rect::from_min_size(
+                egui::pos2(d.x as f32 / scale, d.y as f32 / scale),
+                egui::vec2(d.width as f32 / scale, d.height as f32 / scale),
+            ),
+            scale: d.scale_factor.max(1.0),
+            primary: d.is_primary,
+        }
+    }).collect()
+}
+
+/// Global mouse position in the same units as `monitors`.
+fn cursor(monitors: &[Monitor]) -> Option<egui::Pos2> {
+    let (x, y) = cursor_raw()?;
+    // X11 and Windows report physical pixels
+    let scale = if cfg!(target_os = "macos") { 1.0 } else { monitors.first().map_or(1.0, |m| m.scale) };
+    Some(egui::pos2(x as f32 / scale, y as f32 / scale))
+}
+
+#[cfg(target_os = "linux")]
+fn cursor_raw() -> Option<(i32, i32)> {
+    let (conn, screen) = xcb::Connection::connect(None).ok()?;
+    let root = conn.get_setup().roots().nth(screen as usize)?.root();
+    let reply = conn.wait_for_reply(conn.send_request(&xcb::x::QueryPointer { window: root })).ok()?;
+    Some((reply.root_x() as i32, reply.root_y() as i32))
+}
+
+#[cfg(target_os = "macos")]
+fn cursor_raw() -> Option<(i32, i32)> {
+    use core_graphics::event::CGEvent;
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+    let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState).ok()?;
+    let point = CGEvent::new(source).ok()?.location();
+    Some((point.x as i32, point.y as i32))
+}
+
+#[cfg(windows)]
+fn cursor_raw() -> Option<(i32, i32)> {
+    use windows::Win32::{Foundation::POINT, UI::WindowsAndMessaging::GetCursorPos};
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some((point.x, point.y))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn cursor_raw() -> Option<(i32, i32)> {
+    None
+}
+
+/// What we remember about the window on one display.
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+struct Geometry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<[f32; 2]>,
+    /// Only set once the user has resized the window themselves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<[f32; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Saved {
+    displays: BTreeMap<String, Geometry>,
+}
+
+impl Saved {
+    fn path() -> Option<PathBuf> {
+        paths::state_dir().map(|d| d.join("window.json"))
+    }
+
+    fn load() -> Self {
+        Self::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no state directory")
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Where the window opens and how big, worked out before it exists.
+#[derive(Clone)]
+pub struct Placement {
+    /// The display the window opens on, when displays could be listed.
+    display: Option<String>,
+    pub position: Option<egui::Pos2>,
+    pub size: egui::Vec2,
+    /// Size the user chose on an earlier run; the window then stops
+    /// fitting itself to its content.
+    pub user_size: bool,
+    /// Largest size the window grows to when fitting its content.
+    pub max_size: egui::Vec2,
+}
+
+impl Placement {
+    pub fn new(position: Option<Position>, monitor: Option<MonitorChoice>) -> Self {
+        let monitors = monitors();
+        let cursor = cursor(&monitors);
+        let chosen = match monitor {
+            Some(MonitorChoice::Index(n)) => monitors.get(n - 1),
+            Some(MonitorChoice::Primary) => monitors.iter().find(|m| m.primary),
+            None => None,
+        };
+        let Some(display) = chosen
+            .or_else(|| cursor.and_then(|c| monitors.iter().find(|m| m.rect.contains(c))))
+            .or_else(|| monitors.iter().find(|m| m.primary))
+            .or(monitors.first())
+        else {
+            // Nothing to go on (e.g. Wayland, where clients can't place
+            // windows anyway): leave it to the window manager
+            return Self {
+                display: None,
+                position: None,
+                size: DEFAULT_SIZE,
+                user_size: false,
+                max_size: egui::vec2(f32::INFINITY, f32::INFINITY),
+            };
+        };
+
+        let saved = Saved::load().displays.get(&display.key()).copied().unwrap_or_default();
+        let area = display.rect.shrink(MARGIN);
+        let size = saved.size.map(egui::Vec2::from).unwrap_or(DEFAULT_SIZE).min(area.size()).max(MIN_SIZE);
+
+        let position = match position {
+            Some(Position::Center) => area.center() - size / 2.0,
+            Some(Position::Cursor) => cursor.unwrap_or(area.center()) - egui::vec2(size.x / 2.0, 16.0),
+            Some(Position::TopRight) => egui::pos2(area.max.x - size.x, area.min.y),
+            Some(Position::At(x, y)) => display.rect.min + egui::vec2(x, y),
+            None => saved.position.map(egui::Pos2::from).unwrap_or(area.center() - size / 2.0),
+        };
+        // Keep the whole window on the display
+        let position = position.clamp(area.min, (area.max - size).max(area.min));
+
+        Self {
+            display: Some(display.key()),
+            position: Some(position),
+            size,
+            user_size: saved.size.is_some(),
+            max_size: area.size(),
+        }
+    }
+
+    /// A window that only exists offscreen, `size` points large; it fits
+    /// its content unless `fixed`.
+    pub fn offscreen(size: egui::Vec2, fixed: bool) -> Self {
+        Self {
+            display: None,
+            position: None,
+            size,
+            user_size: fixed,
+            max_size: egui::Vec2::INFINITY,
+        }
+    }
+
+    /// Remembers where the window ended up, and its size if the user
+    /// resized it, for the next run on the same display.
+    pub fn remember(&self, position: Option<egui::Pos2>, size: Option<egui::Vec2>) {
+        let Some(display) = &self.display else { return };
+        let mut saved = Saved::load();
+        let entry = saved.displays.entry(display.clone()).or_default();
+        if let Some(p) = position {
+            entry.position = Some([p.x, p.y]);
+        }
+        if let Some(s) = size {
+            entry.size = Some([s.x, s.y]);
+        }
+        if let Err(e) = saved.save() {
+            eprintln!("Warning: could not save window geometry: {}", e);
+        }
+    }
+}
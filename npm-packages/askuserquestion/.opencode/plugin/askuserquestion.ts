@@ -56,25 +56,42 @@ function getBinaryPath(): string {
 interface QuestionOption {
   label: string
   description: string
+  value?: string
+  recommended?: boolean
+  default?: boolean
+  group?: string
+  image?: string
 }
 
 interface Question {
+  id?: string
   question: string
   header: string
-  options: QuestionOption[]
+  type?: "choice" | "path" | "matrix" | "date" | "time" | "datetime" | "duration" | "secret"
+  options?: QuestionOption[]
+  rows?: QuestionOption[]
   multiSelect: boolean
+  rememberKey?: string
+  submitOnSelect?: boolean
+  allowOther?: boolean
+  mode?: "file" | "directory" | "save"
+  baseDir?: string
+  filter?: string
+  mustExist?: boolean
+  image?: string
+  default?: string
+  min?: string
+  max?: string
+  timezone?: string
 }
 
-interface QuestionAnswer {
-  question: string
-  header: string
-  selected?: string | string[]
-  selected_index?: number | number[]
-}
-
+// Requested with answersFormat "keyed": answers are keyed by question id
+// (or header, or question text) and hold option values (or labels).
 interface BinaryResponse {
-  status: "selected" | "cancelled"
-  answers: QuestionAnswer[]
+  status: "selected" | "partial" | "cancelled"
+  answers: Record<string, string | string[]>
+  // Comments the user added to their answers, keyed like answers
+  notes?: Record<string, string>
 }
 
 async function playNotificationSound(): Promise<void> {
@@ -100,18 +117,86 @@ async function playNotificationSound(): Promise<void> {
   })
 }
 
-async function askUserQuestions(questions: Question[]): Promise<{
-  status: "selected" | "cancelled" | "error"
-  answers: Record<string, string | string[]>
-  raw?: QuestionAnswer[]
-  error?: string
-}> {
+interface Requester {
+  sessionId?: string
+  agentId?: string
+  // Shown in the dialog's banner, window title and the notification
+  title?: string
+  context?: string
+  source?: { agent?: string; project?: string; session?: string }
+}
+
+// A toast through the WinRT API that ships with Windows PowerShell, posted
+// as PowerShell itself since unregistered apps can't show toasts. The text
+// comes in through the environment so it needs no quoting.
+const WINDOWS_TOAST = `
+$m = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]
+$t = $m::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$x = $t.GetElementsByTagName('text')
+[void]$x.Item(0).AppendChild($t.CreateTextNode($env:ASKUSER_TITLE))
+[void]$x.Item(1).AppendChild($t.CreateTextNode($env:ASKUSER_BODY))
+$id = '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe'
+$m::CreateToastNotifier($id).Show([Windows.UI.Notifications.ToastNotification]::new($t))
+`
+
+// Pops up a desktop notification saying who is asking; best effort, like
+// the sound.
+function showNotification(title: string, body: string): void {
+  let command: string
+  let args: string[]
+  let env = process.env
+
+  if (process.platform === "darwin") {
+    command = "osascript"
+    args = ["-e", `display notification ${JSON.stringify(body)} with title ${JSON.stringify(title)}`]
+  } else if (process.platform === "win32") {
+    command = "powershell"
+    args = ["-NoProfile", "-NonInteractive", "-Command", WINDOWS_TOAST]
+    env = { ...process.env, ASKUSER_TITLE: title, ASKUSER_BODY: body }
+  } else {
+    command = "notify-send"
+    args = ["--app-name=askuserquestion", title, body]
+  }
+
+  const proc = spawn(command, args, { stdio: "ignore", detached: true, env })
+  proc.on("error", () => {})
+  proc.unref()
+}
+
+function notify(input: { questions?: Question[]; confirm?: Confirm } & Requester): void {
+  playNotificationSound()
+  const who = [input.source?.agent ?? input.agentId, input.source?.project?.split(/[\\/]/).pop()]
+    .filter(Boolean)
+    .join(" · ")
+  const title = input.title ?? (who ? `Question from ${who}` : "Question")
+  const asked = input.confirm?.title ?? input.questions?.[0]?.question ?? ""
+  showNotification(title, input.title && who ? `${who}: ${asked}` : asked)
+}
+
+interface Confirm {
+  title: string
+  body?: string
+  command?: string
+  diff?: string
+  danger?: boolean
+  confirmLabel?: string
+  denyLabel?: string
+  typeToConfirm?: string
+}
+
+interface ConfirmResponse {
+  status: "approved" | "denied"
+}
+
+// Writes the input to a temp file, runs the binary on it and parses what it
+// prints; null when it printed nothing.
+async function runBinary<T>(input: { questions?: Question[]; confirm?: Confirm } & Requester): Promise<T | null> {
   const binaryPath = getBinaryPath()
   const tempFile = join(tmpdir(), `askuserquestion-${randomUUID()}.json`)
 
   try {
-    writeFileSync(tempFile, JSON.stringify({ questions }))
-    playNotificationSound()
+    writeFileSync(tempFile, JSON.stringify(input))
+    notify(input)
 
     const result = await new Promise<string>((resolve, reject) => {
       const proc = spawn(binaryPath, ["--input", tempFile], {
@@ -136,40 +221,56 @@ async function askUserQuestions(questions: Question[]): Promise<{
     })
 
     const trimmed = result.trim()
-    if (!trimmed) {
-      return { status: "cancelled", answers: {} }
-    }
+    return trimmed ? JSON.parse(trimmed) : null
+  } finally {
+    try {
+      unlinkSync(tempFile)
+    } catch {}
+  }
+}
 
-    const response: BinaryResponse = JSON.parse(trimmed)
+async function askUserQuestions(questions: Question[], requester: Requester = {}): Promise<{
+  status: "selected" | "partial" | "cancelled" | "error"
+  answers: Record<string, string | string[]>
+  notes?: Record<string, string>
+  error?: string
+}> {
+  try {
+    const response = await runBinary<BinaryResponse>({ questions, answersFormat: "keyed", ...requester })
 
-    if (response.status === "cancelled") {
+    if (!response || response.status === "cancelled") {
       return { status: "cancelled", answers: {} }
     }
 
-    const answers: Record<string, string | string[]> = {}
-    for (const answer of response.answers) {
-      const key = answer.header || answer.question
-      answers[key] = answer.selected ?? ""
+    return { status: response.status, answers: response.answers, ...(response.notes && { notes: response.notes }) }
+  } catch (error) {
+    return {
+      status: "error",
+      answers: {},
+      error: error instanceof Error ? error.message : "Unknown error",
     }
+  }
+}
 
-    return { status: "selected", answers, raw: response.answers }
+async function confirmAction(confirm: Confirm, requester: Requester = {}): Promise<{
+  status: "approved" | "denied" | "error"
+  error?: string
+}> {
+  try {
+    const response = await runBinary<ConfirmResponse>({ confirm, ...requester })
+    return { status: response?.status ?? "denied" }
   } catch (error) {
     return {
       status: "error",
-      answers: {},
       error: error instanceof Error ? error.message : "Unknown error",
     }
-  } finally {
-    try {
-      unlinkSync(tempFile)
-    } catch {}
   }
 }
 
 /**
- * OpenCode plugin that registers the AskUserQuestion tool
+ * OpenCode plugin that registers the AskUserQuestion and ConfirmAction tools
  */
-export const AskUserQuestionPlugin: Plugin = async (_ctx) => {
+export const AskUserQuestionPlugin: Plugin = async (ctx) => {
   return {
     tool: {
       AskUserQuestion: tool({
@@ -180,13 +281,22 @@ export const AskUserQuestionPlugin: Plugin = async (_ctx) => {
 4. Offer choices to the user about what direction to take.
 
 Usage notes:
-- Users will always be able to select "Other" to provide custom text input
+- Users can select "Other" to provide custom text input, unless you set allowOther: false because the answer must come from the list
 - Use multiSelect: true to allow multiple answers to be selected for a question
-- If you recommend a specific option, make that the first option in the list and add "(Recommended)" at the end of the label`,
+- If you recommend a specific option, make that the first option in the list and set recommended: true on it (do not add "(Recommended)" to the label)
+- Set default: true on options that should be preselected
+- Use type: "path" (with mode, baseDir and filter) to have the user pick a file or folder instead of listing paths as options
+- Use type: "matrix" with rows to ask the same question about several items at once, e.g. keep/migrate/delete for each of several services
+- Use type: "date", "time", "datetime" or "duration" for scheduling questions instead of asking for free text; answers come back in ISO 8601
+- Use type: "secret" for passwords, tokens and API keys; the field is masked and the answer is never recorded, logged or remembered`,
         args: {
           questions: tool.schema
             .array(
               tool.schema.object({
+                id: tool.schema
+                  .string()
+                  .optional()
+                  .describe("Stable identifier for this question; answers are keyed by it instead of the header."),
                 question: tool.schema
                   .string()
                   .describe(
@@ -197,31 +307,188 @@ Usage notes:
                   .describe(
                     'Very short label displayed as a chip/tag (max 12 chars). Examples: "Auth method", "Library", "Approach".'
                   ),
+                type: tool.schema
+                  .enum(["choice", "path", "matrix", "date", "time", "datetime", "duration", "secret"])
+                  .optional()
+                  .describe('"choice" (default) picks from options; "path" shows a file browser and returns the picked path; "matrix" picks from options for each of rows, in a grid; "date", "time", "datetime" and "duration" show a picker and return ISO 8601 (e.g. "2026-03-14", "09:30", "2026-03-14T09:30:00+01:00", "PT1H30M"); "secret" shows a masked field for a password or token.'),
                 options: tool.schema
                   .array(
                     tool.schema.object({
                       label: tool.schema
                         .string()
                         .describe("The display text for this option (1-5 words)."),
+                      value: tool.schema
+                        .string()
+                        .optional()
+                        .describe("Stable identifier returned instead of the label when this option is chosen."),
                       description: tool.schema
                         .string()
                         .describe("Explanation of what this option means."),
+                      recommended: tool.schema
+                        .boolean()
+                        .optional()
+                        .describe("Show a \"Recommended\" badge on this option."),
+                      default: tool.schema
+                        .boolean()
+                        .optional()
+                        .describe("Preselect this option when the question is shown."),
+                      group: tool.schema
+                        .string()
+                        .optional()
+                        .describe("Section heading to list this option under, for long option lists."),
+                      image: tool.schema
+                        .string()
+                        .optional()
+                        .describe("Picture shown in the option's card: a PNG, JPEG or SVG file path, or base64 data."),
                     })
                   )
                   .min(2)
-                  .max(4)
-                  .describe("The available choices (2-4 options). No 'Other' option needed - it's added automatically."),
+                  .optional()
+                  .describe(
+                    "Required for choice and matrix questions. The available choices, usually 2-4. Longer lists (e.g. branches or files) are shown with a filter box. No 'Other' option needed - it's added automatically. In matrix questions these are the grid's columns."
+                  ),
+                rows: tool.schema
+                  .array(
+                    tool.schema.object({
+                      label: tool.schema
+                        .string()
+                        .describe("The item this row asks about, e.g. a service name."),
+                      value: tool.schema
+                        .string()
+                        .optional()
+                        .describe("Stable identifier the row's answer is keyed by instead of the label."),
+                      description: tool.schema
+                        .string()
+                        .optional()
+                        .describe("More about the item, shown on hover."),
+                    })
+                  )
+                  .optional()
+                  .describe("Required for matrix questions. Each row is answered with one of the options (several with multiSelect); answers come back as an object keyed by row."),
                 multiSelect: tool.schema
                   .boolean()
                   .describe("Set to true to allow multiple selections."),
+                rememberKey: tool.schema
+                  .string()
+                  .optional()
+                  .describe(
+                    'Stable key for questions you may ask again in this project (e.g. "package-manager"). The user can tick "Remember my answer" and later questions with the same key are answered automatically.'
+                  ),
+                submitOnSelect: tool.schema
+                  .boolean()
+                  .optional()
+                  .describe("Single-select only: move on as soon as the user clicks an option, for quick either/or questions."),
+                allowOther: tool.schema
+                  .boolean()
+                  .optional()
+                  .describe('Set to false to hide the free-text "Other" choice when the answer must be one of the options.'),
+                mode: tool.schema
+                  .enum(["file", "directory", "save"])
+                  .optional()
+                  .describe('Path questions: pick an existing "file" (default), an existing "directory", or a file to "save" that may not exist yet.'),
+                baseDir: tool.schema
+                  .string()
+                  .optional()
+                  .describe("Path questions: folder to start browsing in and to resolve relative paths against. Defaults to the working directory."),
+                filter: tool.schema
+                  .string()
+                  .optional()
+                  .describe('Path questions: glob the file name must match, e.g. "*.toml" or "*.{yml,yaml}".'),
+                mustExist: tool.schema
+                  .boolean()
+                  .optional()
+                  .describe("Path questions: require the path to exist. Defaults to true except in save mode."),
+                image: tool.schema
+                  .string()
+                  .optional()
+                  .describe("Picture shown under the question, e.g. a screenshot or mockup: a PNG, JPEG or SVG file path, or base64 data."),
+                default: tool.schema
+                  .string()
+                  .optional()
+                  .describe('Date, time and duration questions: the answer filled in at first, e.g. "2026-03-14", "09:30", "2026-03-14 09:30" or "1h 30m".'),
+                min: tool.schema
+                  .string()
+                  .optional()
+                  .describe("Date, time and duration questions: earliest (or shortest) answer accepted, written like default."),
+                max: tool.schema
+                  .string()
+                  .optional()
+                  .describe("Date, time and duration questions: latest (or longest) answer accepted, written like default."),
+                timezone: tool.schema
+                  .string()
+                  .optional()
+                  .describe('Datetime questions: IANA time zone the answer is picked in, e.g. "Europe/Paris". Defaults to the user\'s.'),
               })
             )
             .min(1)
             .max(4)
             .describe("Questions to ask the user (1-4 questions)"),
+          title: tool.schema
+            .string()
+            .optional()
+            .describe('Short name for the task you are asking about, e.g. "Set up CI". Shown above the questions and in the window title.'),
+          context: tool.schema
+            .string()
+            .optional()
+            .describe("Why you are asking, in markdown: what you found and what depends on the answer."),
+        },
+        async execute(args, context) {
+          const result = await askUserQuestions(args.questions, {
+            sessionId: context.sessionID,
+            agentId: context.agent,
+            title: args.title,
+            context: args.context,
+            source: { project: ctx.directory },
+          })
+          return JSON.stringify(result)
+        },
+      }),
+      ConfirmAction: tool({
+        description: `Ask the user to approve or deny a single action before you take it, such as running a command or applying a change. Returns status "approved" or "denied"; only proceed when approved.
+
+Usage notes:
+- Put the exact command in command, or the change as a unified diff in diff, so the user sees what they are approving
+- Set danger: true for destructive or irreversible actions
+- For especially risky actions, set typeToConfirm to a word the user must type before approving`,
+        args: {
+          title: tool.schema
+            .string()
+            .describe('The question, e.g. "Delete the build directory?"'),
+          body: tool.schema
+            .string()
+            .optional()
+            .describe("Why you want to do this and what it will affect."),
+          command: tool.schema
+            .string()
+            .optional()
+            .describe("Command that will be run, shown verbatim."),
+          diff: tool.schema
+            .string()
+            .optional()
+            .describe("Unified diff of the change that will be applied."),
+          danger: tool.schema
+            .boolean()
+            .optional()
+            .describe("Style the prompt as destructive."),
+          confirmLabel: tool.schema
+            .string()
+            .optional()
+            .describe('Label of the approve button (default "Approve").'),
+          denyLabel: tool.schema
+            .string()
+            .optional()
+            .describe('Label of the deny button (default "Deny").'),
+          typeToConfirm: tool.schema
+            .string()
+            .optional()
+            .describe("Word the user must type before the approve button is enabled."),
         },
-        async execute(args) {
-          const result = await askUserQuestions(args.questions)
+        async execute(args, context) {
+          const result = await confirmAction(args, {
+            sessionId: context.sessionID,
+            agentId: context.agent,
+            source: { project: ctx.directory },
+          })
           return JSON.stringify(result)
         },
       }),